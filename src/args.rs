@@ -0,0 +1,141 @@
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use serde::de::IntoDeserializer;
+
+use manifold_hangman::Settings;
+use manifold_hangman::hangman::{Difficulty, WordListPath};
+use manifold_hangman::strategy::Strategy;
+
+// Anything given here wins over the settings file.
+#[derive(Debug, Parser)]
+#[command(version, about = "Hangman against an adversary that changes the word as you guess")]
+pub struct Args {
+    #[arg(long, default_value = "settings.toml", help = "Settings file to start from")]
+    pub settings: String,
+    #[arg(long, help = "Word list to use instead of the ones in the settings; give it more than once to merge several")]
+    pub word_list: Vec<String>,
+    #[arg(long, help = "History file to use instead of the one in the settings")]
+    pub history: Option<String>,
+    // these two only matter when starting a new game, instead of being asked
+    #[arg(long, help = "Seed for a new game, as a number or any phrase")]
+    pub seed: Option<String>,
+    #[arg(long, conflicts_with = "seed", help = "Start a new game with a random seed instead of asking for one")]
+    pub random_seed: bool,
+    #[arg(long, help = "Word length for a new game")]
+    pub letters: Option<usize>,
+    #[arg(long, value_name = "FILE", help = "Play the moves in this file, one per line, instead of asking for them")]
+    pub guesses: Option<String>,
+    #[arg(long, help = "Start a new game after each one that's finished, until you quit with \".\"")]
+    pub endless: bool,
+    #[arg(long, help = "Show the word counts behind every guess, i.e. --verbosity 2")]
+    pub verbose: bool,
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=3), help = "0 for quiet up to 3 for everything the adversary does")]
+    pub verbosity: Option<u8>,
+    #[arg(long, value_parser = by_name::<Strategy>, help = "weighted, classic, fair or minimax")]
+    pub strategy: Option<Strategy>,
+    #[arg(long, value_parser = by_name::<Difficulty>, help = "easy, medium or hard")]
+    pub difficulty: Option<Difficulty>,
+    #[arg(long, value_name = "PROOF", help = "Check a finished game's proof file instead of playing")]
+    pub verify: Option<String>,
+    #[arg(long, value_name = "CODE", help = "Play a game code someone shared, with the same dictionary and salt as theirs")]
+    pub import: Option<String>,
+    #[arg(long, value_name = "HISTORY", conflicts_with_all = ["import", "endless", "guesses"], help = "Step through a saved game one move at a time instead of playing")]
+    pub replay: Option<String>,
+    #[arg(long, num_args = 2, value_names = ["A", "B"], help = "Compare two saved games move by move and say where they part")]
+    pub diff: Vec<String>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    #[command(about = "Write a default settings file to edit, then exit")]
+    Init,
+    #[command(about = "List the word lengths the dictionary has words for, and how many")]
+    Lengths,
+}
+
+impl Args {
+    pub fn apply(&self, settings: &mut Settings) {
+        if !self.word_list.is_empty() { settings.word_list_path = WordListPath::Many(self.word_list.clone()); }
+        if let Some(path) = &self.history { settings.history_path = path.clone(); }
+        if self.verbose { settings.verbosity = Some(2); }
+        if let Some(verbosity) = self.verbosity { settings.verbosity = Some(verbosity); }
+        if let Some(strategy) = self.strategy { settings.strategy = strategy; }
+        if let Some(difficulty) = self.difficulty { settings.difficulty = Some(difficulty); }
+    }
+}
+
+// Same names as in the settings file, e.g. "classic" or "hard".
+fn by_name<T: for<'de> Deserialize<'de>>(name: &str) -> Result<T, String> {
+    T::deserialize(name.into_deserializer()).map_err(|e: serde::de::value::Error| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter;
+
+    fn args(line: &[&str]) -> Args {
+        Args::try_parse_from(iter::once("manifold_hangman").chain(line.iter().copied())).unwrap()
+    }
+
+    // What the settings file gives, read back the way main reads it.
+    fn from_file(settings: &Settings) -> Settings {
+        let path = std::env::temp_dir().join(format!("hangman-args-{}-{}.toml", std::process::id(), settings.verbosity_level()));
+        std::fs::write(&path, toml::to_string(settings).unwrap()).unwrap();
+        Settings::from_file(&path.to_string_lossy()).unwrap()
+    }
+
+    fn merged(file: &Settings, line: &[&str]) -> Settings {
+        let mut settings = from_file(file);
+        args(line).apply(&mut settings);
+        settings
+    }
+
+    #[test]
+    fn the_command_line_beats_the_file_beats_the_default() {
+        let default = Settings::default();
+        assert_eq!(merged(&default, &[]).verbosity_level(), 1);
+        assert_eq!(merged(&default, &["--verbose"]).verbosity_level(), 2);
+
+        let file = Settings { verbosity: Some(3), strategy: Strategy::Classic, ..Settings::default() };
+        let settings = merged(&file, &[]);
+        assert_eq!((settings.verbosity_level(), settings.strategy), (3, Strategy::Classic));
+        let settings = merged(&file, &["--verbosity", "0", "--strategy", "fair"]);
+        assert_eq!((settings.verbosity_level(), settings.strategy), (0, Strategy::Fair));
+        assert_eq!(merged(&file, &["--verbose"]).verbosity_level(), 2);
+        // --verbosity says exactly how much, so it wins over --verbose
+        assert_eq!(merged(&file, &["--verbose", "--verbosity", "1"]).verbosity_level(), 1);
+    }
+
+    // There's no length in the settings; without --letters it comes from the history or a prompt.
+    #[test]
+    fn letters_are_only_set_when_given() {
+        assert_eq!(args(&[]).letters, None);
+        assert_eq!(args(&["--letters", "7"]).letters, Some(7));
+        assert!(Args::try_parse_from(["manifold_hangman", "--letters", "seven"]).is_err());
+    }
+
+    #[test]
+    fn paths_on_the_command_line_replace_the_files() {
+        let settings = merged(&Settings::default(), &["--word-list", "a.txt", "--word-list", "b.txt", "--history", "h.json"]);
+        assert_eq!(settings.word_list_path, WordListPath::Many(vec!["a.txt".to_string(), "b.txt".to_string()]));
+        assert_eq!(settings.history_path, "h.json");
+        assert_eq!(merged(&Settings::default(), &[]).history_path, Settings::default().history_path);
+    }
+
+    #[test]
+    fn strategies_and_difficulties_go_by_their_settings_names() {
+        assert_eq!(args(&["--strategy", "minimax"]).strategy, Some(Strategy::Minimax));
+        assert_eq!(args(&["--difficulty", "hard"]).difficulty, Some(Difficulty::Hard));
+        assert!(Args::try_parse_from(["manifold_hangman", "--strategy", "nice"]).is_err());
+    }
+
+    #[test]
+    fn lengths_is_a_command_of_its_own() {
+        assert!(matches!(args(&["lengths"]).command, Some(Command::Lengths)));
+        assert!(matches!(args(&["--settings", "other.toml", "lengths"]).command, Some(Command::Lengths)));
+        assert!(args(&[]).command.is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::error::{HangmanError, Result};
+use crate::game::{Game, GameOutcome};
+use crate::hangman::{self, History, Settings};
+
+// Plays a whole game with no one at the keyboard: guesser looks at the game and
+// picks the next letter, until it's won or lost. Good for trying out evil settings
+// over lots of games. seed is the game's rng_seed, with no salt mixed in.
+// A guesser that repeats itself is an error, since with unlimited lives that would never end.
+pub fn play_auto(word_list: Vec<String>, letter_count: usize, settings: &Settings, seed: u64, mut guesser: impl FnMut(&Game) -> char) -> Result<GameOutcome> {
+    let mut game = Game::new(word_list, History::new(seed, letter_count), settings.clone(), 0)?;
+    while !game.is_over() {
+        let guess = guesser(&game).to_ascii_uppercase();
+        if game.history().guessed_letters().contains(&guess) {
+            return Err(HangmanError::InvalidGuess(format!("The guesser picked {} twice", guess)));
+        }
+        game.guess(guess);
+    }
+    // the loop only exits once the game is over
+    Ok(game.outcome().unwrap())
+}
+
+// Goes for whichever unguessed letter is in the most remaining words.
+pub fn frequency_guesser(game: &Game) -> char {
+    let guessed = game.history().guessed_letters();
+    hangman::best_guess(&game.word_list(), &guessed)
+        // best_guess only knows A-Z, so for anything else take a letter the first word still needs
+        .or_else(|| game.some_answer().chars().find(|c| !guessed.contains(c)))
+        .unwrap_or('A')
+}
+
+// How the frequency bot got on over a batch of games.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimStats {
+    pub games: usize,
+    pub wins: usize,
+    pub total_guesses: usize,
+    pub mean_guesses: f64,
+    // the middle game's guesses, or the mean of the middle two
+    pub median_guesses: f64,
+    // how many games ended with each number of wrong guesses
+    pub wrong_guesses: BTreeMap<u32, usize>,
+}
+
+impl SimStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 { 0.0 } else { self.wins as f64 / self.games as f64 }
+    }
+}
+
+// Runs frequency_guesser over a game for each seed and sums up how it went, for
+// comparing strategies and evil parameters. Without max_wrong_guesses every game is a win.
+pub fn simulate(word_list: &[String], letter_count: usize, settings: &Settings, seeds: impl Iterator<Item=u64>) -> Result<SimStats> {
+    let mut guesses = Vec::new();
+    let mut wins = 0;
+    let mut wrong_guesses = BTreeMap::new();
+    for seed in seeds {
+        let outcome = play_auto(word_list.to_vec(), letter_count, settings, seed, frequency_guesser)?;
+        guesses.push(outcome.guesses.len());
+        if outcome.won { wins += 1; }
+        *wrong_guesses.entry(outcome.wrong_count).or_insert(0) += 1;
+    }
+
+    let games = guesses.len();
+    let total_guesses = guesses.iter().sum();
+    guesses.sort();
+    let mean_guesses = if games == 0 { 0.0 } else { total_guesses as f64 / games as f64 };
+    let median_guesses = match games {
+        0 => 0.0,
+        n if n % 2 == 1 => guesses[n / 2] as f64,
+        n => (guesses[n / 2 - 1] + guesses[n / 2]) as f64 / 2.0,
+    };
+    Ok(SimStats { games, wins, total_guesses, mean_guesses, median_guesses, wrong_guesses })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hangman::Guess;
+    use std::iter;
+
+    const WORDS: [&str; 8] = ["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "YAK"];
+
+    fn words() -> Vec<String> {
+        WORDS.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn the_frequency_bot_plays_the_same_game_every_time() {
+        let outcome = play_auto(words(), 3, &Settings::default(), 7, frequency_guesser).unwrap();
+        assert_eq!(play_auto(words(), 3, &Settings::default(), 7, frequency_guesser).unwrap(), outcome);
+        assert!(outcome.won);
+        assert_eq!(outcome.answer, "COT");
+        assert_eq!(outcome.guesses, [Guess::Letter('O'), Guess::Letter('C'), Guess::Letter('T')]);
+        assert_eq!(outcome.rng_seed, 7);
+    }
+
+    #[test]
+    fn a_guesser_that_repeats_itself_is_an_error() {
+        let result = play_auto(words(), 3, &Settings::default(), 7, |_| 'q');
+        assert!(matches!(result, Err(HangmanError::InvalidGuess(_))));
+    }
+
+    #[test]
+    fn simulated_totals_add_up() {
+        let settings = Settings { max_wrong_guesses: Some(3), ..Settings::default() };
+        let stats = simulate(&words(), 3, &settings, 0..9).unwrap();
+        let games: Vec<GameOutcome> = (0..9).map(|seed| play_auto(words(), 3, &settings, seed, frequency_guesser).unwrap()).collect();
+        assert_eq!(stats.games, 9);
+        assert_eq!(stats.total_guesses, games.iter().map(|game| game.guesses.len()).sum::<usize>());
+        assert_eq!(stats.wins, games.iter().filter(|game| game.won).count());
+        assert_eq!(stats.wrong_guesses.values().sum::<usize>(), 9);
+        assert_eq!(stats.mean_guesses, stats.total_guesses as f64 / 9.0);
+        let mut lengths: Vec<usize> = games.iter().map(|game| game.guesses.len()).collect();
+        lengths.sort();
+        assert_eq!(stats.median_guesses, lengths[4] as f64);
+        assert_eq!(stats.win_rate(), stats.wins as f64 / 9.0);
+    }
+
+    #[test]
+    fn no_seeds_is_no_games() {
+        let stats = simulate(&words(), 3, &Settings::default(), iter::empty()).unwrap();
+        assert_eq!((stats.games, stats.total_guesses, stats.mean_guesses, stats.median_guesses, stats.win_rate()), (0, 0, 0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,1721 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{IsTerminal, Write};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{Level, LevelFilter};
+use anyhow::{Context, Result, Error};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+use manifold_hangman::{Game, GameOutcome, GameStatus, Guess, HangmanError};
+use manifold_hangman::{loading, proof, seed, share, transcript};
+use manifold_hangman::loading::{Categories, Dictionary};
+use manifold_hangman::proof::Proof;
+use manifold_hangman::hangman::{Difficulty, OutputMode, Prompts, RevealMode, Settings, History, GuessSignature, WordWeights, MAX_LETTERS, best_guess, display_signature, guess_entropy, length_histogram, letter_frequencies, letter_len, zero_match_bucket};
+
+// With NDJSON output, stdout is only for the JSON, so everything meant for a person goes to stderr.
+static CHATTER_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+macro_rules! say {
+    () => { write_out("\n", CHATTER_TO_STDERR.load(Ordering::Relaxed)) };
+    ($($arg:tt)*) => { write_out(&format!("{}\n", format_args!($($arg)*)), CHATTER_TO_STDERR.load(Ordering::Relaxed)) };
+}
+
+// Same but without the newline, so it shows up right before we wait for input.
+macro_rules! prompt {
+    ($($arg:tt)*) => { write_out(&format!($($arg)*), CHATTER_TO_STDERR.load(Ordering::Relaxed)) };
+}
+
+// Everything printed goes through here; flushed, since a prompt has no newline to do it.
+// In tests it's kept to look at instead.
+fn write_out(text: &str, to_stderr: bool) {
+    #[cfg(test)]
+    if tests::capture(text) { return; }
+    if to_stderr {
+        eprint!("{}", text);
+    } else {
+        print!("{}", text);
+        let _ = io::stdout().flush();
+    }
+}
+
+// One line of NDJSON output per move.
+#[derive(Debug, Serialize)]
+struct TurnEvent {
+    guess: String,
+    revealed: String,
+    newly_revealed: usize,
+    remaining: usize,
+    wrong_count: u32,
+}
+
+impl TurnEvent {
+    fn emit(&self) {
+        // nothing in here can fail to serialize
+        write_out(&format!("{}\n", serde_json::to_string(self).unwrap()), false);
+    }
+}
+
+// The statistics and the adversary's workings are logged rather than said, the library's
+// included, and verbosity 2 and 3 turn on the info and debug levels. RUST_LOG, if it's set,
+// has the last word. They go wherever the chatter does, without any level or timestamp.
+pub fn init_logging(settings: &Settings) {
+    let to_stderr = settings.output == OutputMode::Ndjson;
+    env_logger::Builder::new()
+        .filter_level(LevelFilter::Trace)
+        .parse_default_env()
+        .format(|buf, record| writeln!(buf, "{}", record.args()))
+        .target(if to_stderr { env_logger::Target::Stderr } else { env_logger::Target::Stdout })
+        .init();
+    set_log_level(settings);
+}
+
+// Also for when the settings get read again; leaves RUST_LOG's level alone.
+fn set_log_level(settings: &Settings) {
+    if std::env::var_os("RUST_LOG").is_some() { return; }
+    log::set_max_level(match settings.verbosity_level() {
+        0 | 1 => LevelFilter::Warn,
+        2 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    });
+}
+
+// Colour only goes to a terminal, so piping the output gives exactly the plain text.
+fn use_color(settings: &Settings) -> bool {
+    settings.color && io::stdout().is_terminal()
+}
+
+// The revealed word with whatever's new since prev_info picked out in green.
+fn highlight_new(revealed: &str, prev_info: &str, color: bool) -> String {
+    if !color { return revealed.to_string(); }
+    revealed.chars().zip(prev_info.chars())
+        .map(|(c, prev)| if c != prev { c.green().bold().to_string() } else { c.to_string() })
+        .collect()
+}
+
+// limit is how many buckets to list, with 0 meaning all of them.
+fn display_guess_statistics(bucket_sizes: &HashMap<GuessSignature, usize>, prev_info: &str, guess: char, color: bool, limit: usize) {
+    let mut pairs: Vec<(usize, GuessSignature)> = bucket_sizes.iter().map(|(sig, n)| (*n, *sig)).collect();
+    let total: usize = pairs.iter().map(|(n, _)| n).sum();
+
+    // reverse sort to sort by word matches highest to lowest,
+    // then whatever reverse signature sort is - it just needs to be consistent
+    pairs.sort_by(|a, b| b.cmp(a));
+    let hidden = if limit == 0 { 0 } else { pairs.len().saturating_sub(limit) };
+    pairs.truncate(pairs.len() - hidden);
+
+    for (i, (n, sig)) in pairs.into_iter().enumerate() {
+        let percent = 100.0 * n as f64 / total as f64;
+        let line = format!("{}: {} ({:.1}%)", highlight_new(&display_signature(sig, prev_info, guess), prev_info, color), n, percent);
+        // the first one is the biggest bucket
+        if color && i == 0 {
+            log::info!("{}", line.bold());
+        } else {
+            log::info!("{}", line);
+        }
+    }
+    if hidden > 0 {
+        log::info!("...and {} more", hidden);
+    }
+}
+
+// For count mode, where showing signatures would give the positions away:
+// how many words there were for each number of the letter.
+fn display_count_statistics(bucket_sizes: &HashMap<GuessSignature, usize>, guess: char) {
+    let mut by_count: BTreeMap<u32, usize> = BTreeMap::new();
+    for (sig, n) in bucket_sizes {
+        *by_count.entry(sig.matches_count()).or_insert(0) += n;
+    }
+    for (count, n) in by_count.iter().rev() {
+        log::info!("{} {}: {}", count, guess, n);
+    }
+}
+
+// Head, body, arms and legs, in the order they get drawn.
+const GALLOWS_PARTS: [(usize, usize, char); 6] = [
+    (2, 2, 'O'),
+    (3, 2, '|'),
+    (3, 1, '/'),
+    (3, 3, '\\'),
+    (4, 1, '/'),
+    (4, 3, '\\'),
+];
+
+// The classic drawing, with the six parts spread over however many misses the game allows.
+// Nothing hangs at 0 misses, and the last leg only goes on at wrong == max.
+fn render_gallows(wrong: u32, max: u32) -> String {
+    let parts = GALLOWS_PARTS.len() as u32;
+    let stage = (wrong.min(max) * parts).checked_div(max).unwrap_or(parts) as usize;
+    let mut rows: Vec<Vec<char>> = [
+        "  +---+",
+        "  |   |",
+        "      |",
+        "      |",
+        "      |",
+        "=========",
+    ].iter().map(|row| row.chars().collect()).collect();
+    for &(row, col, c) in &GALLOWS_PARTS[..stage] {
+        rows[row][col] = c;
+    }
+    rows.iter().map(|row| row.iter().collect::<String>()).collect::<Vec<_>>().join("\n")
+}
+
+// Reads a line into buffer. False means the input has run out (e.g. the end of a pipe,
+// or Ctrl-D), which read_line reports by reading nothing, over and over.
+// In tests the lines can be typed in ahead of time instead.
+fn read_line(buffer: &mut String) -> io::Result<bool> {
+    #[cfg(test)]
+    if let Some(read) = tests::typed(buffer) { return Ok(read); }
+    Ok(io::stdin().read_line(buffer)? > 0)
+}
+
+// Anything goes: a number is used as it is, anything else gets hashed into one.
+// Returns the seed and, if it came from a phrase, the phrase. None if the input ran out.
+fn read_seed(prompts: &Prompts) -> Option<(u64, Option<String>)> {
+    let mut buffer = String::new();
+    loop {
+        buffer.clear();
+        prompt!("{}", prompts.seed);
+        let result = || -> Result<Option<String>> {
+            if !read_line(&mut buffer)? { return Ok(None); }
+            let phrase = buffer.trim();
+            if phrase.is_empty() { return Err(Error::msg("Empty seed")); }
+            Ok(Some(phrase.to_string()))
+        }();
+        match result {
+            Ok(None) => break None,
+            Ok(Some(phrase)) => {
+                let seed = seed::parse_seed(&phrase);
+                break Some((seed, phrase.parse::<u64>().is_err().then_some(phrase)))
+            }
+            Err(_) => say!("{}", prompts.try_again),
+        }
+    }
+}
+
+// e.g. "3, 4 or 6"
+fn list_lengths(lengths: &BTreeMap<usize, usize>) -> String {
+    let lengths: Vec<String> = lengths.keys().map(|l| l.to_string()).collect();
+    match lengths.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+// Only takes lengths that have words, out of lengths (see hangman::length_histogram).
+// None if the input ran out.
+fn read_letter_count(lengths: &BTreeMap<usize, usize>, prompts: &Prompts) -> Option<usize> {
+    let mut buffer = String::new();
+    loop {
+        buffer.clear();
+        prompt!("{}", prompts.letter_count);
+        let result = || -> Result<Option<usize>> {
+            if !read_line(&mut buffer)? { return Ok(None); }
+            Ok(Some(buffer.trim().parse()?))
+        }();
+        match result {
+            Ok(None) => break None,
+            Ok(Some(l)) => {
+                if !(1..=MAX_LETTERS).contains(&l) {
+                    say!("Number of letters must be between 1 and {} inclusive.", MAX_LETTERS);
+                } else if !lengths.contains_key(&l) {
+                    say!("No words of that length; try {}.", list_lengths(lengths));
+                } else {
+                    break Some(l)
+                }
+            }
+            Err(_) => say!("{}", prompts.try_again),
+        }
+    }
+}
+
+// What to start a new game with, from the command line. Whatever's missing gets asked for.
+#[derive(Debug, Default)]
+pub struct StartOptions {
+    pub seed: Option<String>,
+    pub letter_count: Option<usize>,
+    // pick a seed at random rather than asking for one
+    pub random_seed: bool,
+    // a file of moves to play instead of asking for them, one per line
+    pub guesses_path: Option<String>,
+}
+
+// word_list is the whole dictionary, for which lengths there are to pick from.
+// None if the input ran out before we had everything.
+fn initialize_game(word_list: &[String], categories: &Categories, settings: &Settings, start: &StartOptions) -> Result<Option<History>> {
+    say!("No history file found, initializing a new game.");
+    let lengths = playable_lengths(word_list, categories, settings);
+    if let Some(l) = start.letter_count {
+        if !(1..=MAX_LETTERS).contains(&l) {
+            return Err(Error::msg(format!("Number of letters must be between 1 and {} inclusive.", MAX_LETTERS)));
+        }
+        if !lengths.contains_key(&l) {
+            return Err(Error::msg(format!("No words of length {}; try {}.", l, list_lengths(&lengths))));
+        }
+    }
+
+    let (rng_seed, seed_phrase) = if let Some(phrase) = &start.seed {
+        (seed::parse_seed(phrase), phrase.parse::<u64>().is_err().then(|| phrase.clone()))
+    } else if start.random_seed {
+        // it goes in the history like any other, so the game can still be replayed
+        (rand::random(), None)
+    } else if settings.daily {
+        let date = seed::utc_date(SystemTime::now());
+        say!("Daily challenge for {}", date);
+        (seed::daily_seed(&date), None)
+    } else {
+        match read_seed(&settings.prompts) {
+            Some(seed) => seed,
+            None => return Ok(None),
+        }
+    };
+    
+    let letter_count = match settings.difficulty {
+        Some(difficulty) => difficulty_letter_count(difficulty, rng_seed),
+        None => match start.letter_count.or_else(|| {
+            say!("Words come in {} letters.", list_lengths(&lengths));
+            read_letter_count(&lengths, &settings.prompts)
+        }) {
+            Some(l) => l,
+            None => return Ok(None),
+        },
+    };
+
+    let started_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).ok();
+    Ok(Some(History { seed_phrase, started_at, ..History::new(rng_seed, letter_count) }))
+}
+
+// Seeded so the same seed always gets the same length.
+fn difficulty_letter_count(difficulty: Difficulty, rng_seed: u64) -> usize {
+    let (_, _, lengths) = difficulty.parameters();
+    let letter_count = ChaCha12Rng::seed_from_u64(rng_seed).random_range(lengths);
+    say!("{:?} difficulty: {} letters", difficulty, letter_count);
+    letter_count
+}
+
+// Times this session on top of however long the game had already been played for.
+struct Stopwatch {
+    before: u64,
+    since: SystemTime,
+}
+
+impl Stopwatch {
+    fn start(history: &History, now: SystemTime) -> Self {
+        Stopwatch { before: history.elapsed_seconds, since: now }
+    }
+
+    // A clock that's gone backwards just doesn't count.
+    fn elapsed_seconds(&self, now: SystemTime) -> u64 {
+        self.before + now.duration_since(self.since).map(|d| d.as_secs()).unwrap_or(0)
+    }
+}
+
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+// With hide_seed, the seed only goes in the file once the game's over, next to the
+// commitment that was saved in its place until then.
+fn save_history(game: &Game, settings: &Settings, rng_salt: u64, stopwatch: &Stopwatch) -> Result<()> {
+    let verbosity = settings.verbosity_level();
+    if verbosity >= 1 { prompt!("Saving history... "); }
+    let mut history = if settings.save_snapshots { game.history_with_snapshot() } else { game.history().clone() };
+    history.elapsed_seconds = stopwatch.elapsed_seconds(SystemTime::now());
+    if settings.hide_seed {
+        history = if game.is_over() {
+            History { seed_commitment: Some(proof::commitment(rng_salt, history.rng_seed)), ..history }
+        } else {
+            proof::hide_seed(&history, rng_salt)
+        };
+    }
+    history.write_to_file(&settings.history_file())?;
+    if verbosity >= 1 { say!("Done!"); }
+    Ok(())
+}
+
+fn new_game(word_list: Vec<String>, weights: Option<Arc<WordWeights>>, history: History, settings: &Settings, rng_salt: u64) -> Result<Game> {
+    Ok(match weights {
+        Some(weights) => Game::with_weights(word_list, weights, history, settings.clone(), rng_salt)?,
+        None => Game::new(word_list, history, settings.clone(), rng_salt)?,
+    })
+}
+
+// Builds the game from the history, narrating each past guess as it's replayed.
+// With a snapshot there's nothing to narrate, the game just picks up where it was
+// (or quietly replays if the snapshot doesn't fit the dictionary any more).
+fn replay_history(word_list: Vec<String>, weights: Option<Arc<WordWeights>>, mut history: History, settings: &Settings, rng_salt: u64) -> Result<Game> {
+    let verbosity = settings.verbosity_level();
+    if !settings.hide_seed {
+        match &history.seed_phrase {
+            Some(phrase) => log::info!("Seed: \"{}\" ({})", phrase, history.rng_seed),
+            None => log::info!("Seed: {}", history.rng_seed),
+        }
+    }
+    if history.snapshot.is_some() {
+        let game = new_game(word_list, weights, history, settings, rng_salt)?;
+        if verbosity >= 1 { say!("Resuming at {}  Remaining Words: {}", game.shown(), game.remaining()); }
+        return Ok(game);
+    }
+    let guesses = std::mem::take(&mut history.guesses);
+    let mut game = new_game(word_list, weights, history, settings, rng_salt)?;
+    log::info!("{} words of length {}", game.remaining(), game.history().letter_count);
+
+    for (n, guess) in guesses.into_iter().enumerate() {
+        game.apply(guess.clone());
+        if verbosity >= 1 { say!("Guess #{}: {}  Result: {}  Remaining Words: {}", n, guess, game.shown(), game.remaining()); }
+    }
+
+    Ok(game)
+}
+
+// Something typed at the guess prompt: a move, or a command about the game itself.
+enum Input {
+    Move(Guess),
+    Undo,
+    Suggest,
+    Quit,
+}
+
+// A guess is one ascii letter once surrounding whitespace is trimmed, or several
+// to guess the whole word, so "ab" is a word rather than quietly meaning 'A'.
+// "?" asks for a hint, "-" takes back the last move, "!" suggests a letter, "." quits and
+// "*" or "giveup" gives up. ("reveal" would clash with guessing the word REVEAL.)
+fn parse_input(line: &str) -> Result<Input, HangmanError> {
+    let line = line.trim();
+    if line.eq_ignore_ascii_case("giveup") { return Ok(Input::Move(Guess::GiveUp)); }
+    let mut chars = line.chars();
+    let c = chars.next().ok_or(HangmanError::InvalidGuess("Need at least one char".to_string()))?;
+    if chars.next().is_some() {
+        return if line.chars().all(|c| c.is_ascii_alphabetic()) {
+            Ok(Input::Move(Guess::Word(line.to_ascii_uppercase())))
+        } else {
+            Err(HangmanError::InvalidGuess("A word guess has to be all ascii letters.".to_string()))
+        };
+    }
+    if c == '?' {
+        Ok(Input::Move(Guess::Hint))
+    } else if c == '*' {
+        Ok(Input::Move(Guess::GiveUp))
+    } else if c == '-' {
+        Ok(Input::Undo)
+    } else if c == '!' {
+        Ok(Input::Suggest)
+    } else if c == '.' {
+        Ok(Input::Quit)
+    } else if c.is_ascii_alphabetic() {
+        Ok(Input::Move(Guess::Letter(c.to_ascii_uppercase())))
+    } else {
+        Err(HangmanError::InvalidGuess("Need an ascii alphabetic character.".to_string()))
+    }
+}
+
+// The letters so far, alphabetically, with the ones that showed up in the word
+// kept apart from the misses, e.g. "Hits: A T | Misses: E Q | "
+fn guessed_summary(guessed: &[char], revealed: &str) -> String {
+    let mut letters = guessed.to_vec();
+    letters.sort();
+    let (hits, misses): (Vec<char>, Vec<char>) = letters.into_iter().partition(|c| revealed.contains(*c));
+    let join = |letters: Vec<char>| letters.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
+
+    let mut summary = String::new();
+    if !hits.is_empty() { summary += &format!("Hits: {} | ", join(hits)); }
+    if !misses.is_empty() { summary += &format!("Misses: {} | ", join(misses)); }
+    summary
+}
+
+// Where moves come from: typed in, or a guesses file for scripted games.
+pub enum Moves {
+    Typed,
+    Scripted { path: String, lines: std::vec::IntoIter<(usize, String)> },
+}
+
+impl Moves {
+    pub fn new(start: &StartOptions) -> Result<Self> {
+        let Some(path) = &start.guesses_path else { return Ok(Moves::Typed) };
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read guesses file {}", path))?;
+        let lines: Vec<(usize, String)> = content.lines().enumerate().map(|(n, line)| (n + 1, line.to_string())).collect();
+        Ok(Moves::Scripted { path: path.clone(), lines: lines.into_iter() })
+    }
+
+    // summary goes before the prompt, e.g. from guessed_summary. None once there are no moves left.
+    fn next(&mut self, summary: &str, prompts: &Prompts) -> Option<Input> {
+        let Moves::Scripted { path, lines } = self else { return read_input(summary, prompts) };
+        for (n, line) in lines {
+            let line = line.trim();
+            if line.is_empty() { continue; }
+            // spelled out, since a file is easier to read that way
+            let command = if line.eq_ignore_ascii_case("undo") {
+                "-"
+            } else if line.eq_ignore_ascii_case("quit") {
+                "."
+            } else {
+                line
+            };
+            match parse_input(command) {
+                Ok(input) => {
+                    say!("{}{}{}", summary, prompts.next_guess, line);
+                    return Some(input);
+                }
+                Err(e) => say!("Skipping line {} of {}: {}", n, path, e),
+            }
+        }
+        None
+    }
+}
+
+// None if the input ran out.
+fn read_input(summary: &str, prompts: &Prompts) -> Option<Input> {
+    let mut buffer = String::new();
+    loop {
+        buffer.clear();
+        prompt!("{}{}", summary, prompts.next_guess);
+        let result = || -> Result<Option<Input>> {
+            if !read_line(&mut buffer)? { return Ok(None); }
+            Ok(Some(parse_input(&buffer)?))
+        }();
+        match result {
+            Ok(input) => break input,
+            Err(_) => say!("{}", prompts.try_again),
+        }
+    }
+}
+
+// How many words of each length a game can be played with. With the category picked at
+// random that's just the words some category has, since pick_category can't give a game
+// any other; a fixed category's words are all the dictionary has anyway.
+fn playable_lengths(word_list: &[String], categories: &Categories, settings: &Settings) -> BTreeMap<usize, usize> {
+    if !settings.random_category() {
+        return length_histogram(word_list);
+    }
+    let mut counts = BTreeMap::new();
+    for word in word_list.iter().filter(|word| categories.values().any(|words| words.contains(*word))) {
+        *counts.entry(letter_len(word)).or_insert(0) += 1;
+    }
+    counts
+}
+
+// The category for a new game: the one in the settings or, if that's "random", one picked
+// with the seed out of those with words this long. None if the settings don't want one.
+fn pick_category(word_list: &[String], categories: &Categories, settings: &Settings, rng_seed: u64, letter_count: usize) -> Result<Option<String>> {
+    if !settings.random_category() {
+        return Ok(settings.fixed_category());
+    }
+    let fitting: Vec<&String> = categories.iter()
+        .filter(|(_, words)| word_list.iter().any(|word| letter_len(word) == letter_count && words.contains(word)))
+        .map(|(name, _)| name)
+        .collect();
+    if fitting.is_empty() {
+        return Err(Error::msg(format!("No category has words of length {}", letter_count)));
+    }
+    // its own rng, so picking a category doesn't change anything else the seed decides
+    let mut rng = ChaCha12Rng::seed_from_u64(seed::stable_hash(&format!("category {}", rng_seed)));
+    Ok(Some(fitting[rng.random_range(0..fitting.len())].clone()))
+}
+
+// What diff_histories found, with the games called by their file names.
+pub fn print_diff(report: &transcript::DiffReport, a: &str, b: &str) {
+    let moves = if report.shared == 1 { "move" } else { "moves" };
+    if !report.diverged() {
+        say!("{} and {} go the same way for all {} {}.", a, b, report.shared, moves);
+        return;
+    }
+    say!("{} and {} go the same way for {} {}, then:", a, b, report.shared, moves);
+    for (name, turn) in [(a, &report.a), (b, &report.b)] {
+        match turn {
+            Some(turn) => say!("  {}: {}  Result: {}  Remaining Words: {}", name, turn.guess, turn.word_info, turn.remaining),
+            None => say!("  {}: no more moves", name),
+        }
+    }
+    match report.remaining_gap() {
+        Some(0) => say!("Both have as many words left."),
+        Some(gap) if gap > 0 => say!("{} has {} more words left than {}.", a, gap, b),
+        Some(gap) => say!("{} has {} more words left than {}.", b, -gap, a),
+        None => {}
+    }
+}
+
+// e.g. "Winner! The word was CAT"
+fn outcome_message(outcome: &GameOutcome, prompts: &Prompts) -> String {
+    let prompt = if outcome.won {
+        &prompts.won
+    } else if outcome.forfeited {
+        &prompts.gave_up
+    } else {
+        &prompts.lost
+    };
+    Prompts::with_word(prompt, &outcome.answer)
+}
+
+// Goes back over a saved game a move at a time, each shown once Enter is pressed, replayed
+// the same way resuming a game does it. "." or running out of input stops early.
+pub fn step_through(word_list: Vec<String>, weights: Option<Arc<WordWeights>>, mut history: History, settings: &Settings, rng_salt: u64) -> Result<()> {
+    if proof::is_seed_hidden(&history) {
+        return Err(Error::msg("That game's seed stays hidden until it's over, so it can't be replayed yet"));
+    }
+    let guesses = std::mem::take(&mut history.guesses);
+    let saved_answer = history.answer.take();
+    history.snapshot = None;
+    let mut game = new_game(word_list, weights, history, settings, rng_salt)?;
+    if let Some(name) = &game.history().category { say!("Category: {}", name); }
+    say!("{} moves to step through: press Enter for each, or . to stop.", guesses.len());
+    say!("Start: {}  Remaining Words: {}", game.shown(), game.remaining());
+
+    let mut buffer = String::new();
+    for (n, guess) in guesses.into_iter().enumerate() {
+        buffer.clear();
+        prompt!("Guess #{}? ", n + 1);
+        if !read_line(&mut buffer)? || buffer.trim() == "." {
+            say!();
+            say!("Stopped at {}.", game.shown());
+            return Ok(());
+        }
+        let before = game.remaining();
+        game.apply(guess.clone());
+        say!("{}  Result: {}  Kept {} of {} words", guess, game.shown(), game.remaining(), before);
+    }
+    match game.outcome() {
+        Some(outcome) => {
+            say!("{}  Score: {}", outcome_message(&outcome, &settings.prompts), outcome.score);
+            // a different dictionary or different settings from when it was played would do this
+            if saved_answer.as_ref().is_some_and(|answer| *answer != outcome.answer) {
+                say!("The history says it ended on {}, so this isn't how it went.", saved_answer.unwrap());
+            }
+        }
+        None => say!("That's as far as the game has got."),
+    }
+    Ok(())
+}
+
+// The adversary is deterministic given the seed, so replaying every move but the last
+// against the whole dictionary lands exactly where the game was before it.
+// Returns None if there's nothing to undo.
+fn undo(game: &Game, dictionary: &[String], weights: &Option<Arc<WordWeights>>, settings: &Settings, rng_salt: u64) -> Result<Option<Game>> {
+    let mut history = game.history().clone();
+    if history.guesses.pop().is_none() { return Ok(None); }
+    history.snapshot = None;
+    new_game(dictionary.to_vec(), weights.clone(), history, settings, rng_salt).map(Some)
+}
+
+// weights are only passed in when settings.use_frequencies is on.
+// start is only looked at if there's no game in progress, and moves carry on from wherever
+// the last game left them.
+// Returns None if no game got played to the end: the input ran out or the player quit (it's
+// saved and can be picked up later), or the saved game was already finished.
+pub fn play_game(dictionary: &Dictionary, opt_history: Option<History>, rng_salt: u64, start: &StartOptions, moves: &mut Moves) -> Result<Option<GameOutcome>> {
+    let Dictionary { settings, word_list, weights, categories, definitions, .. } = dictionary;
+    CHATTER_TO_STDERR.store(settings.output == OutputMode::Ndjson, Ordering::Relaxed);
+    let mut history = match opt_history {
+        Some(history) => history,
+        None => match initialize_game(word_list, categories, settings, start)? {
+            Some(history) => history,
+            None => {
+                say!();
+                say!("No game started.");
+                return Ok(None);
+            }
+        },
+    };
+    if history.status != GameStatus::InProgress {
+        let result = match history.status {
+            GameStatus::Won => "won",
+            GameStatus::Forfeit => "gave up",
+            _ => "lost",
+        };
+        say!("That game is already over: you {} with a score of {}.", result, history.score.unwrap_or(0));
+        if let Some(answer) = &history.answer { say!("The word was {}.", answer); }
+        say!("Delete {} to start a new one.", settings.history_file());
+        return Ok(None);
+    }
+    if proof::is_seed_hidden(&history) {
+        say!("This game's seed is hidden, so it needs typing in again to carry on.");
+        let rng_seed = match &start.seed {
+            Some(phrase) => seed::parse_seed(phrase),
+            None => match read_seed(&settings.prompts) {
+                Some((rng_seed, _)) => rng_seed,
+                None => {
+                    say!();
+                    say!("No game started.");
+                    return Ok(None);
+                }
+            },
+        };
+        if !proof::reveal_seed(&mut history, rng_seed, rng_salt) {
+            return Err(Error::msg("That isn't the seed this game was started with"));
+        }
+    }
+    // a game that's begun keeps the category it had, whatever the settings say now
+    if history.guesses.is_empty() && history.category.is_none() {
+        history.category = pick_category(word_list, categories, settings, history.rng_seed, history.letter_count)?;
+    }
+    if let Some(name) = &history.category { say!("Category: {}", name); }
+    let word_list = loading::category_words(word_list, categories, history.category.as_deref())?;
+    // kept whole so undo can replay from the start
+    let game_words = word_list.clone();
+    let mut game = replay_history(word_list, weights.clone(), history, settings, rng_salt)?;
+    if settings.proof_path.is_some() || settings.hide_seed {
+        say!("Commitment: {} (the salt behind it comes out once the game's over)", proof::commitment(rng_salt, game.history().rng_seed));
+    }
+    if !game.history().pre_revealed.is_empty() && game.history().guesses.is_empty() {
+        say!("Starting with {}", game.shown());
+    }
+    let stopwatch = Stopwatch::start(game.history(), SystemTime::now());
+    save_history(&game, settings, rng_salt, &stopwatch)?;
+    let color = use_color(settings);
+    let verbosity = settings.verbosity_level();
+    while !game.is_over() {
+        let guessed = game.history().guessed_letters();
+        // only worked out if they're going to be seen
+        if log::log_enabled!(Level::Info) {
+            let words = game.word_list();
+            let frequencies = letter_frequencies(&words, &guessed);
+            log::info!("Words with each letter: {}", frequencies.iter().map(|(c, n)| format!("{} {}", c, n)).collect::<Vec<_>>().join(", "));
+            // letters some word has but that the adversary can probably still deny: with at least
+            // half the words in the miss bucket it's the biggest one, so it's likely to be picked
+            let likely_misses: Vec<String> = frequencies.iter()
+                .filter(|(c, _)| zero_match_bucket(&words, *c).is_some_and(|n| 2 * n >= words.len()))
+                .map(|(c, _)| c.to_string())
+                .collect();
+            if !likely_misses.is_empty() { log::info!("Likely misses: {}", likely_misses.join(" ")); }
+            // how much each letter would tell you if the word were picked at random, most first
+            let mut entropies: Vec<(char, f64)> = frequencies.iter().map(|&(c, _)| (c, guess_entropy(&words, c))).collect();
+            entropies.sort_by(|(c1, e1), (c2, e2)| e2.total_cmp(e1).then(c1.cmp(c2)));
+            log::info!("Bits per letter: {}", entropies.iter().map(|(c, e)| format!("{} {:.2}", c, e)).collect::<Vec<_>>().join(", "));
+        }
+        let moves_before = game.history().guesses.len();
+        let revealed_before = game.shown();
+        let word_info_before = game.revealed().to_string();
+        // how many of the letter a guess found, which in count mode is all that's told of it
+        let mut letters_found = None;
+        // the letters so far are part of where things stand, so quiet leaves them out too
+        let summary = if verbosity >= 1 { guessed_summary(&guessed, game.revealed()) } else { String::new() };
+        let input = match moves.next(&summary, &settings.prompts) {
+            Some(Input::Quit) => None,
+            input => input,
+        };
+        let Some(input) = input else {
+            // every move is saved as it's made, so there's nothing left to lose
+            say!();
+            say!("Game saved, run again to carry on.");
+            return Ok(None);
+        };
+        let undoing = matches!(input, Input::Undo);
+        match input {
+            Input::Undo => match undo(&game, &game_words, weights, settings, rng_salt)? {
+                Some(previous) => {
+                    game = previous;
+                    say!("Undone. Back to {}  Remaining Words: {}", game.shown(), game.remaining());
+                }
+                None => {
+                    say!("Nothing to undo.");
+                    continue;
+                }
+            },
+            // taken care of above
+            Input::Quit => unreachable!(),
+            Input::Suggest => {
+                match best_guess(&game.word_list(), &guessed) {
+                    Some(c) => say!("Try {}: it's in the most words left.", c),
+                    None => say!("No letter left that would help."),
+                }
+                continue;
+            }
+            Input::Move(Guess::Letter(guess)) => {
+                if guessed.contains(&guess) {
+                    say!("You already guessed {}.", guess);
+                    continue;
+                }
+                if !settings.allows(guess) {
+                    say!("{} isn't in play; the letters are {}.", guess, settings.allowed_letters.as_deref().unwrap_or_default().to_uppercase());
+                    continue;
+                }
+                // every word in the one bucket that reveals nothing; the guess reuses these buckets
+                if settings.restrict_impossible && game.buckets(guess).keys().eq([&GuessSignature::empty()]) {
+                    say!("That letter can't be in any remaining word.");
+                    continue;
+                }
+                let result = game.guess(guess);
+                letters_found = Some(result.newly_revealed as usize);
+
+                match settings.reveal_mode {
+                    RevealMode::Positions => if log::log_enabled!(Level::Info) {
+                        display_guess_statistics(&result.bucket_sizes, &word_info_before, guess, color, settings.stats_limit);
+                    },
+                    RevealMode::Count => {
+                        if log::log_enabled!(Level::Info) { display_count_statistics(&result.bucket_sizes, guess); }
+                        match result.newly_revealed {
+                            0 => say!("There's no {}.", guess),
+                            1 => say!("There's one {}.", guess),
+                            n => say!("There are {} {}s.", n, guess),
+                        }
+                    }
+                }
+            }
+            Input::Move(Guess::Word(word)) => {
+                if letter_len(&word) != game.history().letter_count {
+                    say!("That's not {} letters long.", game.history().letter_count);
+                    continue;
+                }
+                if !word.chars().all(|c| settings.allows(c)) {
+                    say!("That has letters that aren't in play; the letters are {}.", settings.allowed_letters.as_deref().unwrap_or_default().to_uppercase());
+                    continue;
+                }
+                if game.solve(&word) {
+                    say!("{} is right!", word);
+                } else {
+                    say!("{} is not the word.", word);
+                }
+            }
+            Input::Move(Guess::GiveUp) => {
+                game.give_up();
+            }
+            Input::Move(Guess::Hint) => {
+                // the loop only runs while something is still hidden, so there's always a hint to give
+                let hint = game.hint().unwrap();
+                say!("Hint: letter {} is {}", hint.position + 1, hint.letter);
+            }
+        }
+        // where things stand after every move unless it's quiet; undo has said already
+        if !undoing && verbosity >= 1 {
+            say!("Result: {}  Remaining Words: {}", highlight_new(&game.shown(), &revealed_before, color), game.remaining());
+        }
+        if settings.output == OutputMode::Ndjson && game.history().guesses.len() > moves_before {
+            TurnEvent {
+                // there's a move to report since the count went up
+                guess: game.history().guesses.last().unwrap().to_string(),
+                revealed: game.shown(),
+                // anything else fills in places, even the ones count mode doesn't show yet
+                newly_revealed: letters_found.unwrap_or_else(|| game.revealed().chars().zip(word_info_before.chars()).filter(|(a, b)| a != b).count()),
+                remaining: game.remaining(),
+                wrong_count: game.wrong_count(),
+            }.emit();
+        }
+        log::info!("Guesses so far: {}", game.history().guesses.iter().map(|g| g.to_string()).collect::<String>());
+        if let Some(max) = settings.max_wrong_guesses.filter(|_| verbosity >= 1) {
+            say!("{}", render_gallows(game.wrong_count(), max));
+            say!("Wrong guesses: {} of {}", game.wrong_count(), max);
+        }
+        save_history(&game, settings, rng_salt, &stopwatch)?;
+    }
+    // the loop only exits once the game is over, so there's always an outcome
+    let outcome = game.outcome().unwrap();
+    say!("{}", outcome_message(&outcome, &settings.prompts));
+    if let Some(definition) = definitions.get(&outcome.answer).filter(|_| outcome.won) {
+        say!("{}: {}", outcome.answer, definition);
+    }
+    say!("Score: {}", outcome.score);
+    say!("Time: {}", format_duration(stopwatch.elapsed_seconds(SystemTime::now())));
+    // the start of the game rather than the guesses, so whoever gets it plays it through themselves
+    let fresh = History {
+        pre_revealed: game.history().pre_revealed.clone(),
+        category: game.history().category.clone(),
+        ..History::new(game.history().rng_seed, game.history().letter_count)
+    };
+    say!("Challenge someone to the same word with --import {}", share::encode_game(&fresh));
+    if settings.hide_seed {
+        // checked against what was actually saved, which is what anyone else would look at
+        let saved = History::from_file(&settings.history_file())?;
+        if saved.is_some_and(|history| proof::check_revealed_seed(&history, rng_salt)) {
+            say!("Seed: {}, which matches the commitment", game.history().rng_seed);
+        } else {
+            say!("Seed: {}, but the saved history doesn't match its commitment", game.history().rng_seed);
+        }
+    }
+    if settings.proof_path.is_some() || settings.transcript_path.is_some() {
+        let records = transcript::transcript(game.history(), &game_words, weights.clone(), settings, rng_salt)?;
+        if let Some(path) = &settings.proof_path {
+            // the game being over means there's a proof to make
+            loading::write_proof(&Proof::new(&game, rng_salt, &records).unwrap(), path)?;
+            say!("Proof written to {}", path);
+        }
+        if let Some(path) = &settings.transcript_path {
+            loading::write_transcript(&records, path)?;
+            say!("Transcript written to {}", path);
+        }
+    }
+    Ok(Some(outcome))
+}
+
+// Wins and losses over an endless session, printed between rounds.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SessionTally {
+    pub wins: u32,
+    pub losses: u32,
+    pub total_score: i64,
+}
+
+impl SessionTally {
+    pub fn record(&mut self, outcome: &GameOutcome) {
+        if outcome.won { self.wins += 1 } else { self.losses += 1 }
+        self.total_score += outcome.score;
+    }
+}
+
+impl fmt::Display for SessionTally {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Wins: {}  Losses: {}  Total score: {}", self.wins, self.losses, self.total_score)
+    }
+}
+
+// The game after one that ended with rng_seed. The seed is derived from the last one so a
+// whole session replays from its first seed, and the length is picked with it, from the
+// difficulty if there is one or else from whatever lengths there are words to play.
+fn next_round(rng_seed: u64, word_list: &[String], categories: &Categories, settings: &Settings) -> Option<History> {
+    let rng_seed = seed::bytes_hash(&rng_seed.to_le_bytes());
+    let letter_count = match settings.difficulty {
+        Some(difficulty) => difficulty_letter_count(difficulty, rng_seed),
+        None => {
+            let lengths: Vec<usize> = playable_lengths(word_list, categories, settings).into_keys().filter(|&l| l <= MAX_LETTERS).collect();
+            if lengths.is_empty() { return None; }
+            lengths[ChaCha12Rng::seed_from_u64(rng_seed).random_range(0..lengths.len())]
+        }
+    };
+    let started_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).ok();
+    Some(History { started_at, ..History::new(rng_seed, letter_count) })
+}
+
+// Plays one game after another for as long as they get finished, stopping when the player
+// quits or the input runs out. reload_settings is called before each new game so edits to
+// the settings file take effect; if it fails the old settings are kept.
+pub fn play_endless(dictionary: &mut Dictionary, opt_history: Option<History>, rng_salt: u64, start: &StartOptions, mut reload_settings: impl FnMut() -> Result<Settings>) -> Result<SessionTally> {
+    let mut tally = SessionTally::default();
+    let mut moves = Moves::new(start)?;
+    let mut history = opt_history;
+    loop {
+        let Some(outcome) = play_game(dictionary, history, rng_salt, start, &mut moves)? else { break };
+        tally.record(&outcome);
+        say!("Session so far: {}", tally);
+        say!();
+
+        match reload_settings().and_then(|settings| Ok(dictionary.reload(settings)?)) {
+            Ok(reloaded) => {
+                let settings = &dictionary.settings;
+                set_log_level(settings);
+                if reloaded.evil_changed {
+                    say!("The adversary's settings changed: evil_exponent {}, evil_factor {}", settings.evil_exponent, settings.evil_factor);
+                }
+                if reloaded.words_reloaded {
+                    say!("Reloaded the word list: {} words", dictionary.word_list.len());
+                }
+            }
+            Err(e) => say!("Couldn't reload the settings, so keeping the old ones: {:#}", e),
+        }
+        let Some(next) = next_round(outcome.rng_seed, &dictionary.word_list, &dictionary.categories, &dictionary.settings) else { break };
+        say!("Next round: {} letters", next.letter_count);
+        history = Some(next);
+    }
+    say!("Session over. {}", tally);
+    Ok(tally)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+    use manifold_hangman::hangman::guess_bucket_sizes;
+
+    thread_local! {
+        // what write_out and the logger have been given, while a test is looking
+        static CAPTURED: RefCell<Option<String>> = const { RefCell::new(None) };
+        // everything logged since captured started, with its level
+        static LOGGED: RefCell<Vec<(log::Level, String)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    thread_local! {
+        // lines for read_line to read instead of stdin, while a test is typing
+        static TYPED: RefCell<Option<VecDeque<String>>> = const { RefCell::new(None) };
+    }
+
+    // None if nothing's been typed, so stdin gets read after all. Otherwise whether there
+    // was a line left, the way read_line says whether the input has run out.
+    pub(super) fn typed(buffer: &mut String) -> Option<bool> {
+        TYPED.with_borrow_mut(|typed| typed.as_mut().map(|lines| match lines.pop_front() {
+            Some(line) => { buffer.push_str(&line); buffer.push('\n'); true }
+            None => false,
+        }))
+    }
+
+    // Runs f with these lines for input and returns everything it printed.
+    fn typing(lines: &[&str], f: impl FnOnce()) -> String {
+        TYPED.set(Some(lines.iter().map(|line| line.to_string()).collect()));
+        let output = captured(f);
+        TYPED.set(None);
+        output
+    }
+
+    // False if nothing's capturing, so it gets printed after all.
+    pub(super) fn capture(text: &str) -> bool {
+        CAPTURED.with_borrow_mut(|captured| match captured {
+            Some(captured) => { captured.push_str(text); true }
+            None => false,
+        })
+    }
+
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool { true }
+        fn log(&self, record: &log::Record) {
+            LOGGED.with_borrow_mut(|logged| logged.push((record.level(), record.args().to_string())));
+            capture(&format!("{}\n", record.args()));
+        }
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger;
+    // the log level and where chatter goes are global, so games take turns
+    static PLAYING: Mutex<()> = Mutex::new(());
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    // A directory of its own for each game's files.
+    fn scratch_dir() -> PathBuf {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!("hangman-cli-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Settings whose files are all somewhere of their own.
+    fn settings() -> Settings {
+        let dir = scratch_dir();
+        Settings::builder()
+            .history_path(dir.join("history.toml").to_string_lossy())
+            .build()
+            .unwrap()
+    }
+
+    fn dictionary(settings: Settings, word_list: Vec<String>) -> Dictionary {
+        Dictionary { settings, word_list, weights: None, letter_count: None, categories: Categories::new(), definitions: HashMap::new() }
+    }
+
+    // Runs f and returns everything it printed or logged.
+    fn captured(f: impl FnOnce()) -> String {
+        let _turn = PLAYING.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = log::set_logger(&LOGGER);
+        CAPTURED.set(Some(String::new()));
+        LOGGED.take();
+        f();
+        CAPTURED.take().unwrap()
+    }
+
+    // Plays a new game with seed 1 of letter_count letters, making these moves.
+    fn play(dictionary: &Dictionary, letter_count: usize, moves: &[&str]) -> (String, Option<GameOutcome>) {
+        let guesses_path = scratch_dir().join("guesses.txt");
+        fs::write(&guesses_path, moves.join("\n")).unwrap();
+        let start = StartOptions { seed: Some("1".to_string()), letter_count: Some(letter_count), guesses_path: Some(guesses_path.to_string_lossy().to_string()), ..StartOptions::default() };
+        let mut outcome = None;
+        let output = captured(|| {
+            set_log_level(&dictionary.settings);
+            let mut moves = Moves::new(&start).unwrap();
+            outcome = play_game(dictionary, None, 0, &start, &mut moves).unwrap();
+        });
+        (output, outcome)
+    }
+
+    const ANIMALS: [&str; 7] = ["CAT", "DOG", "COW", "OWL", "EMU", "YAK", "PIG"];
+    // every letter they have, so a game of them always gets won
+    const ANIMAL_LETTERS: [&str; 15] = ["E", "O", "A", "U", "I", "C", "T", "D", "G", "W", "L", "M", "K", "Y", "P"];
+
+    fn play_at_verbosity(verbosity: u8) -> String {
+        let settings = Settings { verbosity: Some(verbosity), max_wrong_guesses: Some(20), ..settings() };
+        play(&dictionary(settings, words(&ANIMALS)), 3, &ANIMAL_LETTERS).0
+    }
+
+    #[test]
+    fn quiet_leaves_out_where_things_stand() {
+        let output = play_at_verbosity(0);
+        for line in ["Result:", "Saving history", "Wrong guesses", "+---+", "Hits:", "Misses:", "Words with each letter"] {
+            assert!(!output.contains(line), "{:?} at verbosity 0 in:\n{}", line, output);
+        }
+        assert!(output.contains("Score:"));
+    }
+
+    #[test]
+    fn verbosity_1_shows_where_things_stand() {
+        let output = play_at_verbosity(1);
+        for line in ["Result:", "Saving history... Done!", "Wrong guesses", "+---+", "Misses:"] {
+            assert!(output.contains(line), "no {:?} at verbosity 1 in:\n{}", line, output);
+        }
+        for line in ["Words with each letter", "Bits per letter", "kept bucket"] {
+            assert!(!output.contains(line), "{:?} at verbosity 1 in:\n{}", line, output);
+        }
+    }
+
+    #[test]
+    fn verbosity_2_adds_the_statistics() {
+        let output = play_at_verbosity(2);
+        for line in ["Result:", "Words with each letter", "Bits per letter", "Guesses so far"] {
+            assert!(output.contains(line), "no {:?} at verbosity 2 in:\n{}", line, output);
+        }
+        assert!(!output.contains("kept bucket"), "debug lines at verbosity 2 in:\n{}", output);
+    }
+
+    #[test]
+    fn verbosity_3_adds_the_adversarys_workings() {
+        let output = play_at_verbosity(3);
+        for line in ["Words with each letter", "kept bucket"] {
+            assert!(output.contains(line), "no {:?} at verbosity 3 in:\n{}", line, output);
+        }
+    }
+
+    // Count mode never shows where the letters are, but the events still say how many there were.
+    #[test]
+    fn ndjson_counts_letters_found_in_count_mode() {
+        let settings = Settings { output: OutputMode::Ndjson, reveal_mode: RevealMode::Count, ..settings() };
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &ANIMAL_LETTERS);
+        let answer = outcome.unwrap().answer;
+        let events: Vec<serde_json::Value> = output.lines()
+            .filter(|line| line.starts_with('{'))
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert!(!events.is_empty());
+        for event in &events {
+            let guess = event["guess"].as_str().unwrap();
+            assert_eq!(event["newly_revealed"].as_u64().unwrap() as usize, answer.matches(guess).count(), "{} in {}", guess, answer);
+        }
+        assert!(events.iter().any(|event| event["newly_revealed"].as_u64().unwrap() > 0));
+    }
+
+    fn categories(lists: &[(&str, &[&str])]) -> Categories {
+        lists.iter().map(|(name, list)| (name.to_string(), list.iter().map(|w| w.to_string()).collect())).collect()
+    }
+
+    // A random category can only be one with words of the game's length, so the rounds of an
+    // endless session mustn't go for lengths only uncategorized words have.
+    #[test]
+    fn endless_rounds_only_pick_lengths_a_category_has() {
+        let list = words(&["CAT", "DOG", "FIG", "CRANE", "SLATE", "PLANTS"]);
+        let categories = categories(&[("ANIMALS", &["CAT", "DOG"]), ("FRUIT", &["FIG"])]);
+        let random = Settings { category: Some("random".to_string()), ..settings() };
+        for rng_seed in 0..40 {
+            assert_eq!(next_round(rng_seed, &list, &categories, &random).unwrap().letter_count, 3);
+        }
+        let lengths: Vec<usize> = (0..40).map(|rng_seed| next_round(rng_seed, &list, &categories, &settings()).unwrap().letter_count).collect();
+        assert!(lengths.contains(&5), "without a category every length is fair game");
+    }
+
+    // The category picked is shown, the words come from it, and the challenge code keeps it
+    // so whoever imports it plays the same words.
+    #[test]
+    fn random_categories_are_shown_and_kept_in_the_code() {
+        let list = words(&["CAT", "DOG", "FIG", "YAM", "SKY"]);
+        let categories = categories(&[("ANIMALS", &["CAT", "DOG"]), ("FRUIT", &["FIG", "YAM"])]);
+        let settings = Settings { category: Some("random".to_string()), max_wrong_guesses: Some(26), ..settings() };
+        let dictionary = Dictionary { categories: categories.clone(), ..dictionary(settings, list) };
+        let letters: Vec<String> = ('A'..='Z').map(String::from).collect();
+        let (output, outcome) = play(&dictionary, 3, &letters.iter().map(String::as_str).collect::<Vec<_>>());
+
+        let name = output.lines().find_map(|line| line.strip_prefix("Category: ")).expect("no category shown");
+        assert!(categories[name].contains(&outcome.unwrap().answer), "{} isn't in {}", output, name);
+        let code = output.lines().find_map(|line| line.split("--import ").nth(1)).unwrap();
+        assert_eq!(share::decode_game(code).unwrap().category.as_deref(), Some(name));
+    }
+
+    #[test]
+    fn a_length_with_no_words_is_an_error_not_a_panic() {
+        let dictionary = dictionary(settings(), words(&ANIMALS));
+        let start = StartOptions { seed: Some("1".to_string()), letter_count: Some(5), ..StartOptions::default() };
+        let mut error = None;
+        captured(|| {
+            let mut moves = Moves::new(&start).unwrap();
+            error = play_game(&dictionary, None, 0, &start, &mut moves).err();
+        });
+        assert_eq!(error.unwrap().to_string(), "No words of length 5; try 3.");
+    }
+
+    #[test]
+    fn repeated_guesses_are_refused_without_a_turn_going() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let mut moves = vec!["E", "e"];
+        moves.extend(&ANIMAL_LETTERS[1..]);
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &moves);
+        assert!(output.contains("You already guessed E."), "{}", output);
+        let outcome = outcome.unwrap();
+        assert_eq!(outcome.guesses.iter().filter(|&guess| *guess == Guess::Letter('E')).count(), 1);
+        assert!(outcome.won);
+    }
+
+    #[test]
+    fn the_summary_shows_what_has_been_guessed() {
+        assert_eq!(guessed_summary(&['T', 'E', 'A', 'Q'], "_AT"), "Hits: A T | Misses: E Q | ");
+        assert_eq!(guessed_summary(&['E'], "___"), "Misses: E | ");
+        assert_eq!(guessed_summary(&[], "___"), "");
+    }
+
+    #[test]
+    fn guesses_take_the_whole_line() {
+        assert!(matches!(parse_input("a"), Ok(Input::Move(Guess::Letter('A')))));
+        assert!(matches!(parse_input(" a \n"), Ok(Input::Move(Guess::Letter('A')))));
+        // more than one letter is a guess at the word, never just its first letter
+        assert!(matches!(parse_input("ab"), Ok(Input::Move(Guess::Word(word))) if word == "AB"));
+        assert!(matches!(parse_input("1"), Err(HangmanError::InvalidGuess(_))));
+        assert!(matches!(parse_input("a1"), Err(HangmanError::InvalidGuess(_))));
+        assert!(matches!(parse_input(""), Err(HangmanError::InvalidGuess(_))));
+        assert!(matches!(parse_input("   \n"), Err(HangmanError::InvalidGuess(_))));
+    }
+
+    #[test]
+    fn running_out_of_lives_says_what_the_word_was() {
+        let settings = Settings { max_wrong_guesses: Some(2), ..settings() };
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &["Q", "X", "E"]);
+        let outcome = outcome.unwrap();
+        assert!(!outcome.won);
+        assert_eq!(outcome.guesses.len(), 2);
+        assert!(output.contains(&format!("You lose! The word was {}", outcome.answer)), "{}", output);
+    }
+
+    #[test]
+    fn difficulties_pick_a_length_in_their_range() {
+        captured(|| for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let (_, _, lengths) = difficulty.parameters();
+            let picked: Vec<usize> = (0..30).map(|rng_seed| difficulty_letter_count(difficulty, rng_seed)).collect();
+            assert!(picked.iter().all(|l| lengths.contains(l)), "{:?} picked {:?}", difficulty, picked);
+            assert!(picked.iter().any(|&l| l != picked[0]), "{:?} always picked {}", difficulty, picked[0]);
+            assert_eq!(difficulty_letter_count(difficulty, 5), picked[5]);
+        });
+    }
+
+    // Each prompt lists the letters guessed before it, hits apart from misses.
+    #[test]
+    fn prompts_show_the_letters_guessed_so_far() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &ANIMAL_LETTERS);
+        let answer = outcome.unwrap().answer;
+        let prompts: Vec<&str> = output.split("Next guess? ").collect();
+        // the text before each prompt ends with its summary
+        for (n, before) in prompts[..prompts.len() - 1].iter().enumerate() {
+            let summary = before.rsplit('\n').next().unwrap();
+            let guessed: Vec<char> = ANIMAL_LETTERS[..n].iter().map(|g| g.chars().next().unwrap()).collect();
+            let (mut hits, mut misses): (Vec<char>, Vec<char>) = guessed.iter().partition(|c| answer.contains(**c));
+            hits.sort();
+            misses.sort();
+            for (label, letters) in [("Hits: ", hits), ("Misses: ", misses)] {
+                let listed = letters.iter().map(char::to_string).collect::<Vec<_>>().join(" ");
+                assert_eq!(summary.contains(&format!("{}{} |", label, listed)), !letters.is_empty(), "prompt {}: {:?}", n, summary);
+            }
+        }
+    }
+
+    fn strip_colors(text: &str) -> String {
+        let mut plain = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
+    #[test]
+    fn color_only_adds_escapes() {
+        assert_eq!(highlight_new("CA_", "C__", false), "CA_");
+        let colored = highlight_new("CA_", "C__", true);
+        assert!(colored.contains('\x1b'));
+        assert_eq!(strip_colors(&colored), "CA_");
+        assert!(!use_color(&Settings { color: false, ..settings() }));
+    }
+
+    // With color off the statistics are exactly the plain text they always were.
+    #[test]
+    fn statistics_without_color_are_plain_text() {
+        let sizes: HashMap<GuessSignature, usize> = HashMap::from([(GuessSignature::empty(), 3), (GuessSignature::from_bits(0b100), 1)]);
+        let show = |color| captured(|| {
+            log::set_max_level(log::LevelFilter::Info);
+            display_guess_statistics(&sizes, "C__", 'T', color, 0);
+        });
+        assert_eq!(show(false), "C__: 3 (75.0%)\nC_T: 1 (25.0%)\n");
+        assert_eq!(strip_colors(&show(true)), show(false));
+    }
+
+    // How many of the figure's parts are up, not counting the post on the right.
+    fn parts_drawn(gallows: &str) -> usize {
+        gallows.lines().skip(2).take(3).map(|row| row.chars().filter(|c| "O|/\\".contains(*c)).count() - 1).sum()
+    }
+
+    #[test]
+    fn the_gallows_start_empty_and_end_with_the_whole_figure() {
+        assert_eq!(render_gallows(0, 6), "  +---+\n  |   |\n      |\n      |\n      |\n=========");
+        assert_eq!(render_gallows(6, 6), "  +---+\n  |   |\n  O   |\n /|\\  |\n / \\  |\n=========");
+        // whatever the limit, the last miss finishes him and more than that changes nothing
+        for max in [1, 3, 6, 10] {
+            assert_eq!(parts_drawn(&render_gallows(0, max)), 0);
+            assert_eq!(render_gallows(max, max), render_gallows(6, 6));
+            assert_eq!(render_gallows(max + 2, max), render_gallows(6, 6));
+            let drawn: Vec<usize> = (0..=max).map(|wrong| parts_drawn(&render_gallows(wrong, max))).collect();
+            assert!(drawn.windows(2).all(|w| w[0] <= w[1]), "{:?} for max {}", drawn, max);
+            if max < 6 { assert!(drawn[1] > 0); }
+        }
+        assert_eq!(parts_drawn(&render_gallows(1, 3)), 2);
+    }
+
+    // The adversary does the same again from the same seed, so replaying all but the last
+    // move gets back to just where the game was.
+    #[test]
+    fn undo_goes_back_to_before_the_guess() {
+        let settings = settings();
+        let list = words(&ANIMALS);
+        let mut game = new_game(list.clone(), None, History::new(3, 3), &settings, 0).unwrap();
+        assert!(undo(&game, &list, &None, &settings, 0).unwrap().is_none());
+        game.guess('O');
+        let (revealed, remaining) = (game.revealed().to_string(), game.remaining());
+        game.guess('E');
+        let undone = undo(&game, &list, &None, &settings, 0).unwrap().unwrap();
+        assert_eq!(undone.revealed(), revealed);
+        assert_eq!(undone.remaining(), remaining);
+        assert_eq!(undone.history().guesses, [Guess::Letter('O')]);
+    }
+
+    #[test]
+    fn undo_with_nothing_to_undo_says_so() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let mut moves = vec!["-", "E", "-"];
+        moves.extend(&ANIMAL_LETTERS);
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &moves);
+        assert!(output.contains("Nothing to undo."), "{}", output);
+        assert!(output.contains("Undone. Back to ___  Remaining Words: 7"), "{}", output);
+        assert_eq!(outcome.unwrap().guesses[0], Guess::Letter('E'));
+    }
+
+    // ANIMALS has O in three words and nothing else in more than two.
+    #[test]
+    fn suggesting_gives_advice_without_taking_a_turn() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let mut moves = vec!["!"];
+        moves.extend(&ANIMAL_LETTERS);
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &moves);
+        assert!(output.contains("Try O: it's in the most words left."), "{}", output);
+        assert_eq!(outcome.unwrap().guesses[0], Guess::Letter('E'));
+    }
+
+    // At the start every letter's missing from at least half of ANIMALS, O included.
+    #[test]
+    fn verbose_output_marks_the_likely_misses() {
+        let output = play_at_verbosity(2);
+        let first = output.lines().find(|line| line.contains("Likely misses:")).unwrap();
+        assert!(first.contains(" O"), "{}", first);
+        assert!(!play_at_verbosity(1).contains("Likely misses:"));
+    }
+
+    // The moves run out after two guesses, as piped input would.
+    #[test]
+    fn input_ending_midgame_saves_and_stops() {
+        let settings = settings();
+        let path = settings.history_file();
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &["E", "O"]);
+        assert!(outcome.is_none());
+        assert!(output.ends_with("Game saved, run again to carry on.\n"), "{}", output);
+        let saved = History::from_file(&path).unwrap().unwrap();
+        assert_eq!(saved.guesses, [Guess::Letter('E'), Guess::Letter('O')]);
+        assert_eq!(saved.status, GameStatus::InProgress);
+    }
+
+    // Nothing typed comes back out; the prompt just gets asked again.
+    #[test]
+    fn bad_answers_get_asked_again_without_an_echo() {
+        let mut history = None;
+        let output = typing(&["", "hunter2", "x", "9", "3"], || {
+            history = initialize_game(&words(&ANIMALS), &Categories::new(), &settings(), &StartOptions::default()).unwrap();
+        });
+        let history = history.unwrap();
+        assert_eq!((history.rng_seed, history.seed_phrase.as_deref(), history.letter_count), (seed::parse_seed("hunter2"), Some("hunter2"), 3));
+        assert_eq!(output.matches("Random seed? ").count(), 2);
+        assert_eq!(output.matches("Number of letters? ").count(), 3);
+        assert_eq!(output.matches("I couldn't read that, try again.").count(), 2);
+        assert!(output.contains("No words of that length; try 3."), "{}", output);
+        assert!(!output.contains("hunter2"), "{}", output);
+        assert!(!output.lines().any(|line| line.trim() == "x" || line.trim() == "9"), "{}", output);
+    }
+
+    #[test]
+    fn running_out_of_input_at_a_prompt_starts_nothing() {
+        let mut history = Some(History::new(0, 0));
+        typing(&["hunter2"], || {
+            history = initialize_game(&words(&ANIMALS), &Categories::new(), &settings(), &StartOptions::default()).unwrap();
+        });
+        assert_eq!(history, None);
+    }
+
+    // The clock's handed in, so these are moments of our choosing.
+    #[test]
+    fn elapsed_time_only_goes_up_and_carries_on_after_resuming() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let history = History { elapsed_seconds: 30, ..History::new(1, 3) };
+        let stopwatch = Stopwatch::start(&history, start);
+        let saves: Vec<u64> = [0, 5, 12, 12, 100].iter().map(|&s| stopwatch.elapsed_seconds(start + Duration::from_secs(s))).collect();
+        assert_eq!(saves, [30, 35, 42, 42, 130]);
+        // a clock that's gone back doesn't take any time off
+        assert_eq!(stopwatch.elapsed_seconds(start - Duration::from_secs(10)), 30);
+
+        let resumed = Stopwatch::start(&History { elapsed_seconds: 130, ..history }, start + Duration::from_secs(5000));
+        assert_eq!(resumed.elapsed_seconds(start + Duration::from_secs(5007)), 137);
+    }
+
+    #[test]
+    fn durations_read_as_seconds_minutes_and_hours() {
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(137), "2m 17s");
+        assert_eq!(format_duration(7380), "2h 3m");
+    }
+
+    #[test]
+    fn a_finished_game_reports_how_it_went_instead_of_playing() {
+        let settings = settings();
+        let won = History { status: GameStatus::Won, score: Some(270), answer: Some("COW".to_string()), ..History::new(1, 3) };
+        let start = StartOptions::default();
+        let mut outcome = Some(None);
+        let output = typing(&["E"], || {
+            let mut moves = Moves::new(&start).unwrap();
+            outcome = Some(play_game(&dictionary(settings.clone(), words(&ANIMALS)), Some(won), 0, &start, &mut moves).unwrap());
+        });
+        assert_eq!(outcome, Some(None));
+        assert!(output.contains("That game is already over: you won with a score of 270."), "{}", output);
+        assert!(output.contains("The word was COW."), "{}", output);
+        assert!(!output.contains("Next guess?"), "{}", output);
+    }
+
+    // Played out again on a Game of our own, the same seed has to end up the same.
+    #[test]
+    fn ndjson_events_match_the_game() {
+        let settings = Settings { output: OutputMode::Ndjson, ..settings() };
+        let (output, _) = play(&dictionary(settings.clone(), words(&ANIMALS)), 3, &["E", "O"]);
+        let events: Vec<serde_json::Value> = output.lines()
+            .filter(|line| line.starts_with('{'))
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2, "{}", output);
+
+        let mut game = Game::new(words(&ANIMALS), History::new(seed::parse_seed("1"), 3), settings, 0).unwrap();
+        for (event, guess) in events.iter().zip(['E', 'O']) {
+            let before = game.revealed().to_string();
+            game.guess(guess);
+            let newly_revealed = game.revealed().chars().zip(before.chars()).filter(|(a, b)| a != b).count();
+            assert_eq!(event["guess"], guess.to_string());
+            assert_eq!(event["revealed"], game.shown());
+            assert_eq!(event["newly_revealed"], newly_revealed);
+            assert_eq!(event["remaining"], game.remaining());
+            assert_eq!(event["wrong_count"], game.wrong_count());
+        }
+    }
+
+    // Nine words without an A and eleven each with A in a different set of places.
+    #[test]
+    fn statistics_stop_at_the_limit_with_percentages() {
+        let mut list = vec!["BBBB".to_string(); 9];
+        list.extend((1..=11).map(|mask: u32| (0..4).map(|i| if mask & (1 << i) != 0 { 'A' } else { 'B' }).collect::<String>()));
+        let sizes = guess_bucket_sizes(&list, 'A');
+        assert_eq!(sizes.len(), 12);
+        let show = |limit| captured(|| {
+            log::set_max_level(LevelFilter::Info);
+            display_guess_statistics(&sizes, "____", 'A', false, limit);
+        });
+
+        let output = show(3);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", output);
+        assert_eq!(lines[0], "____: 9 (45.0%)");
+        assert!(lines[1..3].iter().all(|line| line.ends_with(": 1 (5.0%)")), "{}", output);
+        assert_eq!(lines[3], "...and 9 more");
+        // 0 is no limit at all
+        assert_eq!(show(0).lines().count(), 12);
+        assert!(!show(12).contains("more"));
+    }
+
+    // No animal has a Z in it.
+    #[test]
+    fn impossible_letters_are_only_refused_when_restricted() {
+        let mut moves = vec!["Z"];
+        moves.extend(&ANIMAL_LETTERS);
+        let play_with = |restrict_impossible| {
+            let settings = Settings { restrict_impossible, max_wrong_guesses: Some(20), ..settings() };
+            play(&dictionary(settings, words(&ANIMALS)), 3, &moves)
+        };
+
+        let (output, outcome) = play_with(true);
+        assert!(output.contains("That letter can't be in any remaining word."), "{}", output);
+        assert!(!outcome.unwrap().guesses.contains(&Guess::Letter('Z')));
+
+        let (output, outcome) = play_with(false);
+        assert!(!output.contains("That letter can't be in any remaining word."), "{}", output);
+        let outcome = outcome.unwrap();
+        assert_eq!(outcome.guesses[0], Guess::Letter('Z'));
+        assert!(outcome.wrong_count >= 1);
+    }
+
+    // A phrase seed, so there's a long number to look for in the file.
+    #[test]
+    fn hidden_seeds_are_only_saved_once_the_game_is_over() {
+        let settings = Settings { hide_seed: true, max_wrong_guesses: Some(20), ..settings() };
+        let dictionary = dictionary(settings.clone(), words(&ANIMALS));
+        let path = settings.history_file();
+        let seed = seed::parse_seed("hunter2").to_string();
+        let run = |history: Option<History>, moves: &[&str]| {
+            let guesses_path = scratch_dir().join("guesses.txt");
+            fs::write(&guesses_path, moves.join("\n")).unwrap();
+            let start = StartOptions { seed: Some("hunter2".to_string()), letter_count: Some(3), guesses_path: Some(guesses_path.to_string_lossy().to_string()), ..StartOptions::default() };
+            captured(|| {
+                let mut moves = Moves::new(&start).unwrap();
+                play_game(&dictionary, history, 0, &start, &mut moves).unwrap();
+            })
+        };
+
+        run(None, &["E"]);
+        let midgame = fs::read_to_string(&path).unwrap();
+        assert!(!midgame.contains(&seed) && !midgame.contains("hunter2"), "{}", midgame);
+        let saved = History::from_file(&path).unwrap().unwrap();
+        assert!(proof::is_seed_hidden(&saved));
+
+        let output = run(Some(saved), &ANIMAL_LETTERS[1..]);
+        assert!(output.contains(&format!("Seed: {}, which matches the commitment", seed)), "{}", output);
+        assert!(fs::read_to_string(&path).unwrap().contains(&seed));
+        assert!(proof::check_revealed_seed(&History::from_file(&path).unwrap().unwrap(), 0));
+    }
+
+    // Lines are numbered from 1, blank ones included.
+    #[test]
+    fn guesses_files_play_through_to_a_known_word() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let mut moves = vec!["e", "", "1x", "undo", "#"];
+        moves.extend(&ANIMAL_LETTERS);
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &moves);
+        assert!(output.contains("Skipping line 3 of "), "{}", output);
+        assert!(output.contains("guesses.txt: A word guess has to be all ascii letters."), "{}", output);
+        assert!(output.contains("Skipping line 5 of "), "{}", output);
+        assert!(output.contains("Undone. Back to ___  Remaining Words: 7"), "{}", output);
+        let outcome = outcome.unwrap();
+        assert!(outcome.won);
+        assert_eq!(outcome.answer, "YAK");
+        assert_eq!(outcome.guesses[0], Guess::Letter('E'));
+    }
+
+    // Without --verbose, as the settings file has it by default.
+    #[test]
+    fn every_move_shows_the_word_and_how_many_are_left() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &ANIMAL_LETTERS);
+        let results: Vec<&str> = output.lines().filter(|line| line.starts_with("Result: ")).collect();
+        assert_eq!(results.len(), outcome.unwrap().guesses.len(), "{}", output);
+        assert_eq!(results[0], "Result: ___  Remaining Words: 6");
+        assert!(results.iter().all(|line| line.contains("  Remaining Words: ")));
+        assert!(!output.contains("Words with each letter"), "{}", output);
+    }
+
+    // Every animal in the list is made of these, and Z isn't one of them.
+    #[test]
+    fn guesses_outside_the_allowed_letters_are_refused() {
+        let settings = Settings { allowed_letters: Some("CATDOGW".to_string()), max_wrong_guesses: Some(20), ..settings() };
+        let (output, outcome) = play(&dictionary(settings, words(&["CAT", "DOG", "COW", "TOG"])), 3, &["z", "C", "A", "T", "D", "O", "G", "W"]);
+        assert!(output.contains("Z isn't in play; the letters are CATDOGW."), "{}", output);
+        let outcome = outcome.unwrap();
+        assert!(outcome.won);
+        assert!(!outcome.guesses.contains(&Guess::Letter('Z')));
+    }
+
+    #[test]
+    fn only_lengths_with_words_are_taken() {
+        let mut history = None;
+        let output = typing(&["1", "5", "4"], || {
+            history = initialize_game(&words(&["CAT", "LION", "OX", "DOG"]), &Categories::new(), &settings(), &StartOptions { seed: Some("1".to_string()), ..StartOptions::default() }).unwrap();
+        });
+        assert!(output.contains("Words come in 2, 3 or 4 letters."), "{}", output);
+        assert_eq!(output.matches("No words of that length; try 2, 3 or 4.").count(), 2, "{}", output);
+        assert_eq!(history.unwrap().letter_count, 4);
+    }
+
+    // Nothing's typed, so asking for the seed would have found the input already over.
+    #[test]
+    fn random_seeds_start_without_asking() {
+        let start = StartOptions { random_seed: true, letter_count: Some(3), ..StartOptions::default() };
+        let mut seeds = Vec::new();
+        let output = typing(&[], || {
+            for _ in 0..2 {
+                let history = initialize_game(&words(&ANIMALS), &Categories::new(), &settings(), &start).unwrap().unwrap();
+                assert_eq!((history.letter_count, history.seed_phrase), (3, None));
+                seeds.push(history.rng_seed);
+            }
+        });
+        assert!(!output.contains("Random seed? "), "{}", output);
+        assert_ne!(seeds[0], seeds[1]);
+    }
+
+    // The first round is won, then the settings file is "edited" before the second.
+    #[test]
+    fn settings_changed_between_rounds_take_effect() {
+        let settings = Settings { max_wrong_guesses: Some(20), evil_factor: 1.0, ..settings() };
+        let mut dictionary = dictionary(settings.clone(), words(&ANIMALS));
+        let guesses_path = scratch_dir().join("guesses.txt");
+        fs::write(&guesses_path, [&ANIMAL_LETTERS[..], &["."]].concat().join("\n")).unwrap();
+        let start = StartOptions { seed: Some("1".to_string()), letter_count: Some(3), guesses_path: Some(guesses_path.to_string_lossy().to_string()), ..StartOptions::default() };
+        let edited = Settings { evil_factor: 3.0, ..settings };
+        let mut reloads = 0;
+        let output = captured(|| {
+            play_endless(&mut dictionary, None, 0, &start, || { reloads += 1; Ok(edited.clone()) }).unwrap();
+        });
+        assert_eq!(reloads, 1);
+        assert_eq!(dictionary.settings.evil_factor, 3.0);
+        assert!(output.contains(&format!("The adversary's settings changed: evil_exponent {}, evil_factor 3", edited.evil_exponent)), "{}", output);
+        assert!(!output.contains("Reloaded the word list"), "{}", output);
+        assert!(output.contains("Next round: 3 letters"), "{}", output);
+    }
+
+    #[test]
+    fn chained_rounds_add_up_until_quit() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let mut dictionary = dictionary(settings.clone(), words(&ANIMALS));
+        let guesses_path = scratch_dir().join("guesses.txt");
+        fs::write(&guesses_path, [&ANIMAL_LETTERS[..], &ANIMAL_LETTERS[..], &["."]].concat().join("\n")).unwrap();
+        let start = StartOptions { seed: Some("1".to_string()), letter_count: Some(3), guesses_path: Some(guesses_path.to_string_lossy().to_string()), ..StartOptions::default() };
+        let mut tally = None;
+        let output = captured(|| tally = Some(play_endless(&mut dictionary, None, 0, &start, || Ok(settings.clone())).unwrap()));
+        // YAK and OWL get won, then the third round is quit partway through
+        assert_eq!(tally, Some(SessionTally { wins: 2, losses: 0, total_score: 0 }));
+        assert_eq!(output.matches("Next round: 3 letters").count(), 2, "{}", output);
+        assert!(output.contains("Session so far: Wins: 1  Losses: 0"), "{}", output);
+        assert!(output.trim_end().ends_with("Session over. Wins: 2  Losses: 0  Total score: 0"), "{}", output);
+    }
+
+    #[test]
+    fn giving_up_reveals_a_word_and_saves_a_forfeit() {
+        let settings = settings();
+        let path = settings.history_file();
+        let (output, outcome) = play(&dictionary(settings, words(&ANIMALS)), 3, &["O", "giveup", "E"]);
+        let outcome = outcome.unwrap();
+        assert!(outcome.forfeited && !outcome.won);
+        assert_eq!(outcome.score, 0);
+        assert!(ANIMALS.contains(&outcome.answer.as_str()));
+        assert!(output.contains(&format!("You gave up. The word was {}", outcome.answer)), "{}", output);
+        // the E after giving up never gets played
+        assert_eq!(outcome.guesses, [Guess::Letter('O'), Guess::GiveUp]);
+        let saved = History::from_file(&path).unwrap().unwrap();
+        assert_eq!(saved.status, GameStatus::Forfeit);
+        assert_eq!(saved.answer, Some(outcome.answer));
+    }
+
+    fn french() -> Prompts {
+        Prompts {
+            seed: "Graine ? ".to_string(),
+            letter_count: "Combien de lettres ? ".to_string(),
+            next_guess: "Lettre suivante ? ".to_string(),
+            try_again: "Je n'ai pas compris.".to_string(),
+            won: "Gagné ! C'était {}".to_string(),
+            gave_up: "Abandon. C'était {}".to_string(),
+            lost: "Perdu !".to_string(),
+        }
+    }
+
+    #[test]
+    fn prompts_from_the_settings_are_the_ones_asked() {
+        let settings = Settings { prompts: french(), max_wrong_guesses: Some(20), ..settings() };
+        let output = typing(&["", "hunter2", "x", "3"], || {
+            initialize_game(&words(&ANIMALS), &Categories::new(), &settings, &StartOptions::default()).unwrap();
+        });
+        assert_eq!(output.matches("Graine ? ").count(), 2);
+        assert_eq!(output.matches("Combien de lettres ? ").count(), 2);
+        assert_eq!(output.matches("Je n'ai pas compris.").count(), 2);
+        assert!(!output.contains("Random seed?") && !output.contains("try again"), "{}", output);
+
+        let (output, outcome) = play(&dictionary(settings.clone(), words(&ANIMALS)), 3, &ANIMAL_LETTERS);
+        assert!(output.contains("Lettre suivante ? "), "{}", output);
+        assert!(output.contains(&format!("Gagné ! C'était {}", outcome.unwrap().answer)), "{}", output);
+        assert!(!output.contains("Next guess?") && !output.contains("Winner!"), "{}", output);
+        // without a {} the word goes on the end
+        let lost = GameOutcome { won: false, forfeited: false, answer: "YAK".to_string(), guesses: Vec::new(), wrong_count: 6, score: 0, rng_seed: 1 };
+        assert_eq!(outcome_message(&lost, &settings.prompts), "Perdu ! YAK");
+    }
+
+    #[test]
+    fn verbose_output_lists_letters_by_how_much_they_tell() {
+        let output = play_at_verbosity(2);
+        let first = output.lines().find(|line| line.contains("Bits per letter: ")).unwrap();
+        let bits: Vec<f64> = first.split("Bits per letter: ").nth(1).unwrap().split(", ").map(|entry| entry[2..].parse().unwrap()).collect();
+        assert_eq!(bits.len(), ANIMAL_LETTERS.len());
+        assert!(bits.windows(2).all(|pair| pair[0] >= pair[1]), "{}", first);
+        assert!(!play_at_verbosity(1).contains("Bits per letter"));
+    }
+
+    #[test]
+    fn stepping_through_ends_where_the_game_did() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let path = settings.history_file();
+        let (_, outcome) = play(&dictionary(settings.clone(), words(&ANIMALS)), 3, &ANIMAL_LETTERS);
+        let outcome = outcome.unwrap();
+        let saved = History::from_file(&path).unwrap().unwrap();
+        let moves = saved.guesses.len();
+        let enters = vec![""; moves];
+        let output = typing(&enters, || step_through(words(&ANIMALS), None, saved.clone(), &settings, 0).unwrap());
+        assert_eq!(output.matches("Kept ").count(), moves, "{}", output);
+        let last = output.lines().rfind(|line| line.contains("Result: ")).unwrap();
+        assert!(last.contains(&format!("Result: {}  Kept 1 of", replay_history(words(&ANIMALS), None, saved.clone(), &settings, 0).unwrap().shown())), "{}", last);
+        assert!(output.contains(&format!("Winner! The word was {}  Score: {}", outcome.answer, outcome.score)), "{}", output);
+        assert!(!output.contains("isn't how it went"), "{}", output);
+
+        // . stops after the first move
+        let output = typing(&["", "."], || step_through(words(&ANIMALS), None, saved.clone(), &settings, 0).unwrap());
+        assert_eq!(output.matches("Kept ").count(), 1, "{}", output);
+        assert!(output.contains("Stopped at "), "{}", output);
+        assert!(!output.contains("Winner!"), "{}", output);
+    }
+
+    fn logged_at(verbosity: u8) -> Vec<(log::Level, String)> {
+        play_at_verbosity(verbosity);
+        LOGGED.take()
+    }
+
+    // Statistics are info and the adversary's workings debug; the game itself isn't logged.
+    #[test]
+    fn diagnostics_are_logged_at_their_levels() {
+        let logged = logged_at(3);
+        let level_of = |start: &str| logged.iter().find(|(_, message)| message.starts_with(start)).map(|(level, _)| *level);
+        assert_eq!(level_of("Words with each letter"), Some(log::Level::Info));
+        assert_eq!(level_of("Bits per letter"), Some(log::Level::Info));
+        assert_eq!(level_of("Seed: "), Some(log::Level::Info));
+        assert!(logged.iter().any(|(level, message)| *level == log::Level::Debug && message.contains("kept bucket")), "{:?}", logged);
+        assert!(!logged.iter().any(|(_, message)| message.contains("Winner!") || message.contains("Next guess?")), "{:?}", logged);
+
+        assert!(logged_at(2).iter().all(|(level, _)| *level <= log::Level::Info));
+        assert!(logged_at(1).iter().all(|(level, _)| *level <= log::Level::Warn));
+    }
+
+    // Seed 1 with every letter ends on YAK.
+    #[test]
+    fn a_win_shows_the_definition_if_there_is_one() {
+        let settings = Settings { max_wrong_guesses: Some(20), ..settings() };
+        let defined = |definitions: &[(&str, &str)]| Dictionary {
+            definitions: definitions.iter().map(|(word, definition)| (word.to_string(), definition.to_string())).collect(),
+            ..dictionary(settings.clone(), words(&ANIMALS))
+        };
+        let (output, outcome) = play(&defined(&[("YAK", "a long-haired ox"), ("CAT", "a small cat")]), 3, &ANIMAL_LETTERS);
+        assert_eq!(outcome.unwrap().answer, "YAK");
+        assert!(output.contains("YAK: a long-haired ox"), "{}", output);
+        assert!(!output.contains("CAT:"), "{}", output);
+
+        let (output, _) = play(&defined(&[("CAT", "a small cat")]), 3, &ANIMAL_LETTERS);
+        assert!(!output.contains("YAK:") && !output.contains("CAT:"), "{}", output);
+
+        // only winning earns it
+        let (output, outcome) = play(&defined(&[("YAK", "a long-haired ox")]), 3, &["giveup"]);
+        assert_eq!(outcome.unwrap().answer, "YAK");
+        assert!(!output.contains("long-haired"), "{}", output);
+    }
+}
@@ -0,0 +1,66 @@
+use std::io;
+use thiserror::Error;
+
+// Everything the library can fail with, so callers can tell the kinds apart.
+// The messages read the same as before, with the underlying cause kept as the source.
+#[derive(Debug, Error)]
+pub enum HangmanError {
+    // context says which file and what we were doing with it
+    #[error("{context}")]
+    Io { context: String, #[source] source: io::Error },
+    #[error("{context}")]
+    Parse { context: String, #[source] source: Box<dyn std::error::Error + Send + Sync> },
+    #[error("{0}")]
+    InvalidGuess(String),
+    #[error("{0}")]
+    InvalidSettings(String),
+    #[error("No words of length {letter_count} in the dictionary")]
+    EmptyWordList { letter_count: usize },
+    #[error("No words left to guess against")]
+    NoWordsLeft,
+    #[error("The word list isn't the one this game started with, so it wouldn't replay the same way")]
+    WordListChanged,
+    #[error("History asks for {letter_count} letters, but at most {max} are supported")]
+    TooManyLetters { letter_count: usize, max: usize },
+    #[error("That's not a valid game code: {0}")]
+    InvalidGameCode(String),
+    #[error("History is version {found}, but this only understands up to version {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+}
+
+pub type Result<T, E = HangmanError> = std::result::Result<T, E>;
+
+// only the file loading makes these
+#[cfg(feature = "word-list-loading")]
+impl HangmanError {
+    pub(crate) fn io(context: impl Into<String>, source: io::Error) -> Self {
+        HangmanError::Io { context: context.into(), source }
+    }
+
+    pub(crate) fn parse(context: impl Into<String>, source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        HangmanError::Parse { context: context.into(), source: source.into() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn messages_read_the_same_as_before() {
+        assert_eq!(HangmanError::InvalidGuess("Need at least one char".to_string()).to_string(), "Need at least one char");
+        assert_eq!(HangmanError::EmptyWordList { letter_count: 5 }.to_string(), "No words of length 5 in the dictionary");
+        assert_eq!(HangmanError::TooManyLetters { letter_count: 200, max: 128 }.to_string(), "History asks for 200 letters, but at most 128 are supported");
+    }
+
+    // The context is the message and what went wrong underneath is the source, for {:#} to chain.
+    #[test]
+    fn file_errors_keep_their_cause() {
+        let error = HangmanError::Io { context: "Failed to read settings file s.toml".to_string(), source: io::ErrorKind::NotFound.into() };
+        assert_eq!(error.to_string(), "Failed to read settings file s.toml");
+        let source = error.source().unwrap().downcast_ref::<io::Error>().unwrap();
+        assert_eq!(source.kind(), io::ErrorKind::NotFound);
+        assert!(HangmanError::NoWordsLeft.source().is_none());
+    }
+}
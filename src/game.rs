@@ -0,0 +1,1091 @@
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use crate::error::{HangmanError, Result};
+use std::collections::HashMap;
+use std::iter;
+use std::sync::Arc;
+
+use crate::hangman::{self, Buckets, GameStatus, Guess, GuessSignature, Revealed, RevealMode, Settings, History, Snapshot, WordWeights};
+use crate::seed;
+use crate::strategy::GuessStrategy;
+
+// A single game in progress. Nothing in here reads or prints anything,
+// so any frontend can drive it one guess at a time.
+// R is where the randomness comes from; see with_rng for using something other than ChaCha12.
+pub struct Game<R = ChaCha12Rng> {
+    // the dictionary, which can be shared with other games; see from_shared
+    words: Arc<Vec<String>>,
+    // where in words the words still possible are, in order
+    candidates: Vec<usize>,
+    word_info: String,
+    history: History,
+    rng: R,
+    settings: Settings,
+    strategy: Box<dyn GuessStrategy>,
+    // of the length-filtered dictionary, for checking snapshots against
+    word_list_hash: u64,
+    // the last buckets() asked for, and how many moves in, so guessing that letter next
+    // doesn't bucket the words all over again
+    bucket_cache: Option<(char, usize, Buckets)>,
+}
+
+// Games get handed between threads, so that had better keep working.
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Game>();
+};
+
+// What a single guess did to the game.
+#[derive(Debug, Clone)]
+pub struct GuessResult {
+    pub signature: GuessSignature,
+    pub newly_revealed: u32,
+    pub remaining: usize,
+    // how many words fell into each signature, for anyone who wants to show the odds
+    pub bucket_sizes: HashMap<GuessSignature, usize>,
+}
+
+// What a hint gave away.
+#[derive(Debug, Clone)]
+pub struct HintResult {
+    // counting from 0 at the start of the word
+    pub position: usize,
+    pub letter: char,
+    pub remaining: usize,
+}
+
+// How a finished game turned out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOutcome {
+    pub won: bool,
+    // given up rather than lost, so won is false too
+    pub forfeited: bool,
+    // the revealed word on a win or after giving up, or a word that was still possible on a loss
+    pub answer: String,
+    pub guesses: Vec<Guess>,
+    pub wrong_count: u32,
+    pub score: i64,
+    // the seed the game was played with, for carrying on from it
+    pub rng_seed: u64,
+}
+
+impl Game {
+    // Any guesses already in the history are replayed, so this also resumes a saved game.
+    // If the history has a snapshot that fits this dictionary, it picks up from that instead.
+    // The strategy comes from settings.strategy.
+    pub fn new(word_list: Vec<String>, history: History, settings: Settings, rng_salt: u64) -> Result<Self> {
+        Self::from_shared(Arc::new(word_list), history, settings, rng_salt)
+    }
+
+    // Same as new(), but words are weighted by how common they are.
+    pub fn with_weights(word_list: Vec<String>, weights: Arc<WordWeights>, history: History, settings: Settings, rng_salt: u64) -> Result<Self> {
+        Self::seeded(Arc::new(word_list), history, settings, rng_salt, |settings, words, rng| settings.strategy.build(settings, words, Some(weights), rng))
+    }
+
+    // Same as new(), for a dictionary loaded once and shared by lots of games, e.g. in a
+    // server. The words are never copied: each game only keeps track of which of them it
+    // still has, so the dictionary can have words of any length in it.
+    pub fn from_shared(word_list: Arc<Vec<String>>, history: History, settings: Settings, rng_salt: u64) -> Result<Self> {
+        Self::seeded(word_list, history, settings, rng_salt, |settings, words, rng| settings.strategy.build(settings, words, None, rng))
+    }
+
+    // Same as new(), but with your own adversary in place of settings.strategy.
+    pub fn with_strategy(word_list: Vec<String>, history: History, settings: Settings, rng_salt: u64, strategy: Box<dyn GuessStrategy>) -> Result<Self> {
+        Self::seeded(Arc::new(word_list), history, settings, rng_salt, |_, _, _| strategy)
+    }
+
+    fn seeded<F>(words: Arc<Vec<String>>, mut history: History, settings: Settings, rng_salt: u64, make_strategy: F) -> Result<Self>
+    where F: FnOnce(&Settings, &[&str], &mut dyn RngCore) -> Box<dyn GuessStrategy> {
+        let snapshot = history.snapshot.take();
+        let guesses = std::mem::take(&mut history.guesses);
+        let rng = ChaCha12Rng::seed_from_u64(rng_salt ^ history.rng_seed);
+        let mut game = Self::start(words, history, settings, rng, guesses.is_empty(), make_strategy)?;
+        if let Some(snapshot) = snapshot
+            && game.restore(&snapshot, &guesses) {
+            game.history.guesses = guesses;
+            return Ok(game);
+        }
+        game.replay(guesses);
+        Ok(game)
+    }
+
+    // Jump straight to the snapshot's position, if it was taken from this dictionary
+    // after these guesses. Leaves the game alone and returns false otherwise.
+    fn restore(&mut self, snapshot: &Snapshot, guesses: &[Guess]) -> bool {
+        let Ok(rng_word_pos) = snapshot.rng_word_pos.parse() else { return false };
+        if snapshot.word_list_hash != self.word_list_hash
+            || snapshot.guess_count != guesses.len()
+            || snapshot.words.is_empty()
+            || hangman::letter_len(&snapshot.word_info) != self.history.letter_count
+            // a different blank from last time means the saved word_info can't be read any more
+            || !snapshot.word_info.chars().all(|c| c == self.settings.blank() || c.is_alphabetic()) {
+            return false;
+        }
+        // the hash matching means every saved word is one of ours
+        let index: HashMap<&str, usize> = self.candidates.iter().map(|&i| (self.words[i].as_str(), i)).collect();
+        let Some(candidates) = snapshot.words.iter().map(|word| index.get(word.as_str()).copied()).collect() else { return false };
+        self.candidates = candidates;
+        self.word_info = snapshot.word_info.clone();
+        self.rng.set_word_pos(rng_word_pos);
+        true
+    }
+
+    // Everything needed to resume from here without replaying.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            word_list_hash: self.word_list_hash,
+            guess_count: self.history.guesses.len(),
+            word_info: self.word_info.clone(),
+            rng_word_pos: self.rng.get_word_pos().to_string(),
+            words: self.word_list().into_iter().map(String::from).collect(),
+        }
+    }
+
+    // The history with a snapshot of the current position attached.
+    pub fn history_with_snapshot(&self) -> History {
+        History { snapshot: Some(self.snapshot()), ..self.history.clone() }
+    }
+}
+
+impl<R: RngCore> Game<R> {
+    // Bring your own randomness instead of the salted ChaCha12 the other constructors use,
+    // e.g. a fixed sequence to make the adversary do something specific.
+    // The history's rng_seed isn't used, and snapshots are ignored since they only
+    // know how to wind a ChaCha12 forward, so the guesses always get replayed.
+    pub fn with_rng(word_list: Vec<String>, mut history: History, settings: Settings, rng: R) -> Result<Self> {
+        history.snapshot = None;
+        let guesses = std::mem::take(&mut history.guesses);
+        let mut game = Self::start(Arc::new(word_list), history, settings, rng, guesses.is_empty(), |settings, words, rng| settings.strategy.build(settings, words, None, rng))?;
+        game.replay(guesses);
+        Ok(game)
+    }
+
+    // The game with no guesses made yet. fresh is whether it's a new game rather than one
+    // being resumed, which is the only time there are letters to pick for pre-revealing.
+    fn start<F>(words: Arc<Vec<String>>, mut history: History, mut settings: Settings, mut rng: R, fresh: bool, make_strategy: F) -> Result<Self>
+    where F: FnOnce(&Settings, &[&str], &mut dyn RngCore) -> Box<dyn GuessStrategy> {
+        settings.apply_difficulty();
+        let mut candidates = hangman::words_of_length(&words, history.letter_count)?;
+        let word_list_hash = seed::word_list_hash(candidates.iter().map(|&i| &words[i]));
+        if history.word_list_hash.is_some_and(|saved| saved != word_list_hash) {
+            return Err(HangmanError::WordListChanged);
+        }
+        history.word_list_hash = Some(word_list_hash);
+        let word_info = Self::pre_reveal(&words, &mut candidates, &mut history, &settings, fresh)?;
+        // built once the pre-revealed letters have narrowed the words, so a fair answer fits
+        // them, and before any restoring, so it sees the same rng it did when the game began
+        let strategy = make_strategy(&settings, &words_at(&words, &candidates), &mut rng);
+        Ok(Game {
+            words,
+            candidates,
+            word_info,
+            history,
+            rng,
+            settings,
+            strategy,
+            word_list_hash,
+            bucket_cache: None,
+        })
+    }
+
+    // Fills in the history's pre-revealed letters, first picking them if this is a fresh game
+    // and settings.reveal_hints asks for some, and returns the word as it starts out.
+    // They all come from one word, and only words that agree with it in those places are
+    // kept, so the adversary is held to them. The picking has its own rng, from the seed,
+    // so the game's rng is the same either way.
+    // Errors if a saved history's letters leave no word, e.g. from a different dictionary.
+    fn pre_reveal(words: &[String], candidates: &mut Vec<usize>, history: &mut History, settings: &Settings, fresh: bool) -> Result<String> {
+        let letter_count = history.letter_count;
+        if history.pre_revealed.is_empty() && fresh && settings.reveal_hints > 0 {
+            let mut rng = ChaCha12Rng::seed_from_u64(history.rng_seed);
+            let word: Vec<char> = words[candidates[rng.random_range(0..candidates.len())]].chars().collect();
+            let count = settings.reveal_hints.min(letter_count - 1);
+            let mut positions = rand::seq::index::sample(&mut rng, letter_count, count).into_vec();
+            positions.sort();
+            history.pre_revealed = positions.into_iter().map(|position| Revealed { position, letter: word[position] }).collect();
+        }
+
+        let mut word_info: Vec<char> = iter::repeat_n(settings.blank(), letter_count).collect();
+        for &Revealed { position, letter } in &history.pre_revealed {
+            candidates.retain(|&i| words[i].chars().nth(position) == Some(letter));
+            let Some(c) = word_info.get_mut(position) else { return Err(HangmanError::NoWordsLeft) };
+            *c = letter;
+        }
+        if candidates.is_empty() { return Err(HangmanError::NoWordsLeft); }
+        Ok(word_info.into_iter().collect())
+    }
+
+    fn replay(&mut self, guesses: Vec<Guess>) {
+        self.history.wrong_count = 0;    // the replay works these out again
+        self.history.score = None;
+        self.history.status = GameStatus::InProgress;
+        for guess in guesses {
+            self.apply(guess);
+        }
+    }
+
+    // Make whatever move this is. Handy for replaying a history.
+    pub fn apply(&mut self, guess: Guess) {
+        match guess {
+            Guess::Letter(c) => { self.guess(c); }
+            Guess::Hint => { self.hint(); }
+            Guess::Word(w) => { self.solve(&w); }
+            Guess::GiveUp => { self.give_up(); }
+        }
+    }
+
+    // How guessing this letter would split the words left, without guessing it.
+    pub fn buckets(&mut self, guess: char) -> &Buckets {
+        let guess = guess.to_ascii_uppercase();
+        let moves = self.history.guesses.len();
+        if !matches!(&self.bucket_cache, Some((c, m, _)) if *c == guess && *m == moves) {
+            self.bucket_cache = Some((guess, moves, hangman::guess_buckets_among(&self.words, &self.candidates, guess)));
+        }
+        // filled in just above if it wasn't already
+        &self.bucket_cache.as_ref().unwrap().2
+    }
+
+    pub fn guess(&mut self, guess: char) -> GuessResult {
+        let guess = guess.to_ascii_uppercase();
+        let moves = self.history.guesses.len();
+        let cached = match self.bucket_cache.take() {
+            Some((c, m, buckets)) if c == guess && m == moves => Some(buckets),
+            _ => None,
+        };
+        self.strategy.set_revealed(self.revealed_fraction());
+        // new() refuses an empty word list and we always keep a non-empty bucket, so neither can fail
+        let (chosen, bucket_sizes, survivors) = match (cached, self.strategy.by_size()) {
+            // only worth it if the buckets haven't been worked out already
+            (None, Some(strategy)) => {
+                let outcome = hangman::do_guess_by_size(guess, &self.words, &self.candidates, strategy, &mut self.rng)
+                    .expect("word list should never be empty mid-game");
+                (outcome.chosen, outcome.sizes, outcome.survivors)
+            }
+            (cached, _) => {
+                let buckets = cached.unwrap_or_else(|| hangman::guess_buckets_among(&self.words, &self.candidates, guess));
+                let outcome = hangman::do_guess_bucketed(guess, buckets, &self.words, self.strategy.as_ref(), &mut self.rng)
+                    .expect("word list should never be empty mid-game");
+                let sizes = outcome.buckets.iter().map(|(sig, bucket)| (*sig, bucket.len())).collect();
+                (outcome.chosen, sizes, outcome.survivors)
+            }
+        };
+        let newly_revealed = chosen.matches_count();
+
+        self.history.guesses.push(Guess::Letter(guess));
+        if newly_revealed == 0 { self.history.wrong_count += 1; }
+        self.word_info = hangman::display_signature(chosen, &self.word_info, guess);
+        self.candidates = survivors;
+        log::debug!("{}: kept bucket {:b} of {}, {}", guess, chosen, bucket_sizes.len(), sample_words(&self.word_list()));
+        self.record_if_over();
+
+        GuessResult {
+            signature: chosen,
+            newly_revealed,
+            remaining: self.remaining(),
+            bucket_sizes,
+        }
+    }
+
+    // Guess the whole word. Returns whether that won the game.
+    // The strategy gets to decide between the guessed word and everything else, just like
+    // for a letter, so an evil adversary will happily drop your word if it can. Either way
+    // a wrong answer costs a miss.
+    pub fn solve(&mut self, word: &str) -> bool {
+        let word = word.to_uppercase();
+        let (hit, rest): (Vec<usize>, Vec<usize>) = self.candidates.iter().partition(|&&i| self.words[i] == word);
+        let solved = GuessSignature::full(self.history.letter_count);
+        let mut buckets = HashMap::new();
+        if !hit.is_empty() { buckets.insert(solved, hit); }
+        if !rest.is_empty() { buckets.insert(GuessSignature::empty(), rest); }
+
+        self.strategy.set_revealed(self.revealed_fraction());
+        let signature = hangman::choose_guess_outcome(&buckets, &self.words, self.strategy.as_ref(), &mut self.rng)
+            .expect("word list should never be empty mid-game");
+        self.history.guesses.push(Guess::Word(word.clone()));
+        if signature == solved {
+            self.word_info = word;
+        } else {
+            self.history.wrong_count += 1;
+        }
+        self.candidates = buckets.remove(&signature).unwrap();
+        self.record_if_over();
+
+        signature == solved
+    }
+
+    // Reveals one hidden position of a random remaining word, or of the strategy's answer if
+    // it has committed to one, and from then on only words with that letter in that spot
+    // survive, so the adversary has to live with it.
+    // Hints cost the same as a miss. Returns None if there's nothing left to reveal.
+    pub fn hint(&mut self) -> Option<HintResult> {
+        let hidden: Vec<usize> = self.word_info.chars().enumerate()
+            .filter(|(_, c)| *c == self.settings.blank())
+            .map(|(i, _)| i)
+            .collect();
+        if hidden.is_empty() { return None; }
+
+        // picked either way, so the rng goes on the same whatever the strategy
+        let picked = self.rng.random_range(0..self.candidates.len());
+        let position = hidden[self.rng.random_range(0..hidden.len())];
+        let word = self.strategy.committed_answer().unwrap_or(&self.words[self.candidates[picked]]);
+        let letter = word.chars().nth(position).unwrap();
+
+        let words = &self.words;
+        self.candidates.retain(|&i| words[i].chars().nth(position) == Some(letter));
+        self.word_info = self.word_info.chars().enumerate()
+            .map(|(i, c)| if i == position {letter} else {c})
+            .collect();
+        self.history.guesses.push(Guess::Hint);
+        self.history.wrong_count += 1;
+        self.record_if_over();
+
+        Some(HintResult { position, letter, remaining: self.remaining() })
+    }
+
+    // Concedes the game: one of the words left gets picked as the answer and filled in,
+    // and the game ends as a forfeit, which scores nothing. Returns the answer.
+    pub fn give_up(&mut self) -> &str {
+        self.commit_answer();
+        self.word_info = self.some_answer().to_string();
+        self.history.guesses.push(Guess::GiveUp);
+        self.record_if_over();
+        self.some_answer()
+    }
+
+    // Narrows the words left down to one, picked with the game's rng so the same seed and
+    // guesses always end on the same word, and a proof can check it. A strategy that
+    // committed to a word up front gets that one instead.
+    fn commit_answer(&mut self) {
+        if self.candidates.len() > 1 {
+            let picked = self.rng.random_range(0..self.candidates.len());
+            let committed = self.strategy.committed_answer()
+                .and_then(|answer| self.candidates.iter().copied().find(|&i| self.words[i] == answer));
+            self.candidates = vec![committed.unwrap_or(self.candidates[picked])];
+        }
+    }
+
+    // Every word left has to look like what's been revealed: the revealed letters in the
+    // same places, and no letter the player guessed anywhere that's still blank.
+    // Only checked in debug builds, after every move, to catch bucketing going wrong.
+    fn words_fit_revealed(&self) -> bool {
+        let blank = self.settings.blank();
+        let guessed = self.history.guessed_letters();
+        self.word_list().iter().all(|word| {
+            hangman::letter_len(word) == hangman::letter_len(&self.word_info)
+                && word.chars().zip(self.word_info.chars()).all(|(w, r)| if r == blank { !guessed.contains(&w) } else { w == r })
+        })
+    }
+
+    // Once the game's over, the history gets the final score and result.
+    fn record_if_over(&mut self) {
+        debug_assert!(self.words_fit_revealed(), "a word left doesn't fit {}", self.word_info);
+        if self.is_lost() {
+            self.commit_answer();
+        }
+        if self.is_over() {
+            self.history.score = Some(self.score());
+            self.history.answer = Some(self.final_answer().to_string());
+            self.history.status = if self.is_won() {
+                GameStatus::Won
+            } else if self.is_forfeited() {
+                GameStatus::Forfeit
+            } else {
+                GameStatus::Lost
+            };
+        }
+    }
+
+    // How much of the word is showing, from 0 to 1.
+    fn revealed_fraction(&self) -> f64 {
+        let showing = self.word_info.chars().filter(|&c| c != self.settings.blank()).count();
+        showing as f64 / self.history.letter_count as f64
+    }
+
+    // Giving up fills the word in too, but that's not a win.
+    pub fn is_won(&self) -> bool {
+        !self.word_info.contains(self.settings.blank()) && !self.is_forfeited()
+    }
+
+    // Giving up is always the last move, since it ends the game.
+    pub fn is_forfeited(&self) -> bool {
+        self.history.guesses.last() == Some(&Guess::GiveUp)
+    }
+
+    // Running out of lives only counts if the word isn't already solved.
+    pub fn is_lost(&self) -> bool {
+        match self.settings.max_wrong_guesses {
+            Some(max) => !self.is_won() && self.history.wrong_count >= max,
+            None => false,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost() || self.is_forfeited()
+    }
+
+    // None while the game is still going.
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if !self.is_over() { return None; }
+        Some(GameOutcome {
+            won: self.is_won(),
+            forfeited: self.is_forfeited(),
+            answer: self.final_answer().to_string(),
+            guesses: self.history.guesses.clone(),
+            wrong_count: self.history.wrong_count,
+            score: self.score(),
+            rng_seed: self.history.rng_seed,
+        })
+    }
+
+    // What the word turned out to be, for a game that's over: filled in unless it was lost.
+    fn final_answer(&self) -> &str {
+        if self.is_lost() { self.some_answer() } else { self.revealed() }
+    }
+
+    // Losing scores nothing; see hangman::score for how a win is scored.
+    pub fn score(&self) -> i64 {
+        if self.is_won() {
+            hangman::score(self.history.letter_count, self.history.wrong_count, self.history.guesses.len())
+        } else {
+            0
+        }
+    }
+
+    pub fn wrong_count(&self) -> u32 {
+        self.history.wrong_count
+    }
+
+    // Some word that's still consistent with everything revealed so far.
+    // On a loss it's the only one left, see commit_answer, and what we claim the word was all along.
+    pub fn some_answer(&self) -> &str {
+        &self.words[self.candidates[0]]
+    }
+
+    // The word as the player currently sees it, e.g. "_A__T"
+    pub fn revealed(&self) -> &str {
+        &self.word_info
+    }
+
+    // What the player gets to see of the word. That's revealed() unless they're only told
+    // counts, in which case it's blanks until the end, bar any letters given at the start.
+    pub fn shown(&self) -> String {
+        match self.settings.reveal_mode {
+            RevealMode::Count if !self.is_won() => {
+                let mut shown: Vec<char> = iter::repeat_n(self.settings.blank(), self.history.letter_count).collect();
+                // new() made sure these positions are in the word
+                for r in &self.history.pre_revealed { shown[r.position] = r.letter; }
+                shown.into_iter().collect()
+            }
+            _ => self.word_info.clone(),
+        }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    // The words still possible.
+    pub fn word_list(&self) -> Vec<&str> {
+        words_at(&self.words, &self.candidates)
+    }
+
+    // How many words are still possible, without listing them.
+    pub fn remaining(&self) -> usize {
+        self.candidates.len()
+    }
+}
+
+fn words_at<'a>(words: &'a [String], indices: &[usize]) -> Vec<&'a str> {
+    indices.iter().map(|&i| words[i].as_str()).collect()
+}
+
+// The first few words, for logging.
+fn sample_words(words: &[&str]) -> String {
+    const SHOWN: usize = 10;
+    let mut sample = words.iter().take(SHOWN).cloned().collect::<Vec<_>>().join(", ");
+    if words.len() > SHOWN { sample += &format!(" and {} more", words.len() - SHOWN); }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hangman::tests::{on_one_thread, signatures};
+    use crate::strategy::Strategy;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    fn settings() -> Settings {
+        Settings::builder().build().unwrap()
+    }
+
+    // Every letter in turn until the game's over, or the alphabet runs out.
+    fn play_out<R: RngCore>(game: &mut Game<R>) {
+        for c in 'A'..='Z' {
+            if game.is_over() { break; }
+            game.guess(c);
+        }
+    }
+
+    #[test]
+    fn a_length_with_no_words_is_refused() {
+        let result = Game::new(words(&["CAT", "DOG"]), History::new(1, 5), settings(), 0);
+        assert!(matches!(result, Err(HangmanError::EmptyWordList { letter_count: 5 })));
+    }
+
+    #[test]
+    fn accented_words_play_at_their_letter_count() {
+        let mut game = Game::new(words(&["CAFÉ", "NAÏVE", "SOUP"]), History::new(1, 4), settings(), 0).unwrap();
+        assert_eq!(game.word_list(), ["CAFÉ", "SOUP"]);
+        assert_eq!(game.revealed(), "____");
+        game.guess('É');
+        assert_eq!(game.revealed().chars().count(), 4);
+        assert!(game.revealed() == "___É" || game.word_list() == ["SOUP"], "{}", game.revealed());
+    }
+
+    #[test]
+    fn classic_games_keep_the_biggest_bucket() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "ACT"]);
+        let settings = Settings::builder().strategy(Strategy::Classic).build().unwrap();
+        let mut game = Game::new(list, History::new(1, 3), settings, 0).unwrap();
+        assert_eq!(game.guess('T').newly_revealed, 0);
+        assert_eq!(game.word_list(), ["DOG", "PIG", "HEN", "OWL", "EMU"]);
+    }
+
+    // Fair games judge every guess against the word picked at the start.
+    #[test]
+    fn fair_games_reveal_the_committed_words_letters() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE"]);
+        let settings = Settings::builder().strategy(Strategy::Fair).max_wrong_guesses(26).build().unwrap();
+        for rng_seed in 0..6 {
+            let mut game = Game::new(list.clone(), History::new(rng_seed, 5), settings.clone(), 0).unwrap();
+            let answer = game.strategy.committed_answer().unwrap().to_string();
+            for c in ['E', 'Z', 'R', 'A', 'T'] {
+                let shown: String = answer.chars().zip(game.revealed().chars()).map(|(a, r)| if a == c { a } else { r }).collect();
+                let result = game.guess(c);
+                assert_eq!(result.newly_revealed as usize, answer.matches(c).count());
+                assert_eq!(game.revealed(), shown);
+            }
+            play_out(&mut game);
+            assert_eq!(game.outcome().unwrap().answer, answer);
+        }
+    }
+
+    // The friendliest adversary there is, to show any strategy can be plugged in.
+    struct SmallestBucket;
+
+    impl GuessStrategy for SmallestBucket {
+        fn choose(&self, buckets: &Buckets, _words: &[String], _rng: &mut dyn RngCore) -> GuessSignature {
+            *buckets.iter().min_by_key(|&(sig, bucket)| (bucket.len(), *sig)).unwrap().0
+        }
+    }
+
+    #[test]
+    fn games_take_any_strategy() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "ACT"]);
+        let mut game = Game::with_strategy(list, History::new(1, 3), settings(), 0, Box::new(SmallestBucket)).unwrap();
+        // O splits them into COT and DOG, OWL on its own, and the five without one
+        assert_eq!(game.guess('O').newly_revealed, 1);
+        assert_eq!(game.word_list(), ["OWL"]);
+        assert_eq!(game.revealed(), "O__");
+    }
+
+    // Q, X and Z are in none of them, so each is a miss whatever the adversary does.
+    #[test]
+    fn running_out_of_lives_loses_on_a_word_that_fits() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "ACT"]);
+        let settings = Settings::builder().max_wrong_guesses(3).build().unwrap();
+        let mut game = Game::new(list.clone(), History::new(2, 3), settings.clone(), 0).unwrap();
+        for (c, over) in [('Q', false), ('X', false), ('Z', true)] {
+            game.guess(c);
+            assert_eq!(game.is_over(), over, "after {}", c);
+        }
+        assert!(game.is_lost() && !game.is_won());
+        assert_eq!(game.wrong_count(), 3);
+        let answer = game.outcome().unwrap().answer;
+        assert_eq!(game.word_list(), [answer.as_str()]);
+        assert!(list.contains(&answer));
+
+        // the misses are in the history, so a replay ends up lost too
+        let mut history = game.history().clone();
+        history.snapshot = None;
+        let replayed = Game::new(list, history, settings, 0).unwrap();
+        assert!(replayed.is_lost());
+        assert_eq!(replayed.wrong_count(), game.wrong_count());
+    }
+
+    #[test]
+    fn won_games_report_the_word_and_how_it_went() {
+        let settings = Settings::builder().strategy(Strategy::Classic).build().unwrap();
+        let mut game = Game::new(words(&["CAT", "COT"]), History::new(1, 3), settings, 0).unwrap();
+        assert!(game.outcome().is_none());
+        for c in ['T', 'C', 'A', 'O'] { game.guess(c); }
+        let outcome = game.outcome().unwrap();
+        assert!(outcome.won && !outcome.forfeited);
+        // A splits them one each, and classic evil breaks the tie with the miss
+        assert_eq!(outcome.answer, "COT");
+        assert_eq!(outcome.answer, game.revealed());
+        assert_eq!(outcome.guesses, ['T', 'C', 'A', 'O'].map(Guess::Letter));
+        assert_eq!(outcome.wrong_count, 1);
+        assert_eq!(outcome.rng_seed, 1);
+    }
+
+    #[test]
+    fn lost_games_report_a_word_that_was_left() {
+        let settings = Settings::builder().strategy(Strategy::Classic).max_wrong_guesses(2).build().unwrap();
+        let mut game = Game::new(words(&["CAT", "COT", "DOG"]), History::new(1, 3), settings, 0).unwrap();
+        for c in ['E', 'Z'] { game.guess(c); }
+        let outcome = game.outcome().unwrap();
+        assert!(!outcome.won && !outcome.forfeited);
+        assert_eq!(outcome.wrong_count, 2);
+        assert_eq!(outcome.guesses, [Guess::Letter('E'), Guess::Letter('Z')]);
+        assert!(["CAT", "COT", "DOG"].contains(&outcome.answer.as_str()));
+        assert_eq!(outcome.score, 0);
+    }
+
+    // The history gets the score once the game's over, and a game finished after being
+    // resumed scores all its guesses, not just the ones since.
+    #[test]
+    fn the_score_is_kept_in_the_history() {
+        let settings = Settings::builder().strategy(Strategy::Classic).build().unwrap();
+        let list = words(&["CAT", "COT"]);
+        let mut game = Game::new(list.clone(), History::new(1, 3), settings.clone(), 0).unwrap();
+        game.guess('T');
+        game.guess('C');
+        assert_eq!(game.history().score, None);
+
+        let mut resumed = Game::new(list, game.history().clone(), settings, 0).unwrap();
+        resumed.guess('A');
+        resumed.guess('O');
+        // COT, in four guesses with the A missed
+        assert_eq!(resumed.score(), hangman::score(3, 1, 4));
+        assert_eq!(resumed.history().score, Some(resumed.score()));
+        assert_eq!(resumed.outcome().unwrap().score, 220);
+    }
+
+    #[test]
+    fn solving_with_the_last_word_wins() {
+        let settings = Settings::builder().strategy(Strategy::Classic).build().unwrap();
+        let mut game = Game::new(words(&["CAT", "COT", "DOG"]), History::new(1, 3), settings, 0).unwrap();
+        game.guess('G');
+        assert_eq!(game.word_list(), ["CAT", "COT"]);
+        game.guess('A');
+        assert!(game.solve("cot"));
+        assert!(game.is_won());
+        assert_eq!(game.revealed(), "COT");
+        assert_eq!(game.history().guesses.last(), Some(&Guess::Word("COT".to_string())));
+    }
+
+    // Classic evil drops the guessed word whenever there are more words without it.
+    #[test]
+    fn solving_can_be_turned_down_by_the_adversary() {
+        let settings = Settings::builder().strategy(Strategy::Classic).build().unwrap();
+        let mut game = Game::new(words(&["CAT", "COT", "DOG"]), History::new(1, 3), settings, 0).unwrap();
+        assert!(!game.solve("CAT"));
+        assert!(!game.is_over());
+        assert_eq!(game.wrong_count(), 1);
+        assert_eq!(game.word_list(), ["COT", "DOG"]);
+        // a word that isn't there at all is a miss as well
+        assert!(!game.solve("EMU"));
+        assert_eq!(game.wrong_count(), 2);
+    }
+
+    // The way it used to be done, with every bucket a copy of its words.
+    fn cloned_buckets(words: &[String], guess: char) -> HashMap<GuessSignature, Vec<String>> {
+        let mut buckets: HashMap<GuessSignature, Vec<String>> = HashMap::new();
+        for word in words {
+            buckets.entry(hangman::word_signature(word, guess)).or_default().push(word.clone());
+        }
+        buckets
+    }
+
+    // Bucketing by index keeps the same words and picks the same signatures as copying did.
+    #[test]
+    fn index_buckets_play_like_cloned_ones() {
+        use crate::strategy::{ChoosesBySize, ClassicEvil};
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PLANT", "STOMP", "FJORD", "QUICK", "SHORE", "SPOKE"]);
+        let settings = Settings::builder().strategy(Strategy::Classic).build().unwrap();
+        let mut game = Game::new(list.clone(), History::new(1, 5), settings, 0).unwrap();
+        let mut cloned = list;
+        for c in ['E', 'O', 'R', 'A', 'T', 'S'] {
+            let buckets = cloned_buckets(&cloned, c);
+            let sizes = buckets.iter().map(|(sig, bucket)| (*sig, bucket.len())).collect();
+            let chosen = ClassicEvil.choose_by_size(&sizes, &mut ChaCha12Rng::seed_from_u64(0));
+            cloned = buckets[&chosen].clone();
+
+            let result = game.guess(c);
+            assert_eq!(result.signature, chosen, "guessing {}", c);
+            assert_eq!(result.remaining, cloned.len());
+            assert_eq!(game.word_list(), cloned);
+        }
+    }
+
+    // Picking up from a snapshot lands where replaying every guess does, rng and all, without
+    // going over the words again; a snapshot that doesn't fit gets replayed instead.
+    #[test]
+    fn snapshots_resume_like_a_full_replay() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PLANT", "STOMP", "FJORD", "QUICK", "SHORE", "SPOKE"]);
+        let mut game = Game::new(list.clone(), History::new(9, 5), settings(), 0).unwrap();
+        for c in ['E', 'A', 'T'] { game.guess(c); }
+        let saved = game.history_with_snapshot();
+        let replay = History { snapshot: None, ..saved.clone() };
+        let stale = History { snapshot: saved.snapshot.clone().map(|snapshot| Snapshot { word_list_hash: 1, ..snapshot }), ..saved.clone() };
+
+        let resume = |history: History| Game::new(list.clone(), history, settings(), 0).unwrap();
+        let signed = on_one_thread(|| {
+            let start = signatures();
+            resume(saved.clone());
+            signatures() - start
+        });
+        assert_eq!(signed, 0);
+        for history in [replay, stale] {
+            let (mut restored, mut replayed) = (resume(saved.clone()), resume(history));
+            assert_eq!(restored.word_list(), replayed.word_list());
+            assert_eq!(restored.revealed(), replayed.revealed());
+            assert_eq!(restored.guess('R').signature, replayed.guess('R').signature);
+            assert_eq!(restored.guess('O').signature, replayed.guess('O').signature);
+        }
+    }
+
+    #[test]
+    fn reveal_hints_start_with_that_many_letters() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE"]);
+        let settings = Settings::builder().reveal_hints(2).build().unwrap();
+        let game = Game::new(list, History::new(7, 5), settings, 0).unwrap();
+        let shown: Vec<(usize, char)> = game.revealed().chars().enumerate().filter(|&(_, c)| c != '_').collect();
+        assert_eq!(shown.len(), 2);
+        assert_eq!(game.history().pre_revealed.len(), 2);
+        for word in game.word_list() {
+            assert!(shown.iter().all(|&(i, c)| word.chars().nth(i) == Some(c)), "{} doesn't fit {}", word, game.revealed());
+        }
+    }
+
+    // The fair answer has to survive the pre-revealed letters, or the first guess can't find it.
+    // Salted, since with no salt the game's rng and the picking's start out the same.
+    #[test]
+    fn fair_answer_fits_pre_revealed_letters() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU"]);
+        let settings = Settings::builder().strategy(Strategy::Fair).reveal_hints(1).build().unwrap();
+        for rng_seed in 0..20 {
+            let mut game = Game::new(list.clone(), History::new(rng_seed, 3), settings.clone(), 12345).unwrap();
+            play_out(&mut game);
+            assert!(game.is_won(), "seed {} ended on {}", rng_seed, game.revealed());
+        }
+    }
+
+    #[test]
+    fn resumed_games_pick_no_new_letters() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE"]);
+        let settings = Settings::builder().reveal_hints(2).build().unwrap();
+        let history = History { guesses: vec![Guess::Letter('E')], ..History::new(7, 5) };
+        let game = Game::new(list, history, settings, 0).unwrap();
+        assert!(game.history().pre_revealed.is_empty());
+        assert_eq!(game.revealed().chars().filter(|&c| c != '_').count(), game.word_list()[0].matches('E').count());
+    }
+
+    #[test]
+    fn saved_pre_revealed_letters_are_kept() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE"]);
+        let pre_revealed = vec![Revealed { position: 0, letter: 'C' }];
+        let history = History { pre_revealed: pre_revealed.clone(), ..History::new(7, 5) };
+        let game = Game::new(list, history, settings(), 0).unwrap();
+        assert_eq!(game.history().pre_revealed, pre_revealed);
+        assert_eq!(game.revealed(), "C____");
+        assert_eq!(game.word_list().len(), 2);
+    }
+
+    #[test]
+    fn hint_reveals_one_position_and_narrows_the_words() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PRIZE", "QUOTE"]);
+        let mut game = Game::new(list.clone(), History::new(3, 5), settings(), 0).unwrap();
+        let hint = game.hint().unwrap();
+        let shown: Vec<usize> = game.revealed().char_indices().filter(|&(_, c)| c != '_').map(|(i, _)| i).collect();
+        assert_eq!(shown, vec![hint.position]);
+        assert_eq!(hint.remaining, game.word_list().len());
+        assert!(game.word_list().len() < list.len());
+        assert!(game.word_list().iter().all(|w| w.chars().nth(hint.position) == Some(hint.letter)));
+        assert_eq!(game.history().guesses, vec![Guess::Hint]);
+        assert_eq!(game.wrong_count(), 1);
+    }
+
+    // A hint from some other word than the fair answer would rule the answer out.
+    #[test]
+    fn fair_hints_come_from_the_answer() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU"]);
+        let settings = Settings::builder().strategy(Strategy::Fair).build().unwrap();
+        for rng_seed in 0..7 {
+            let mut game = Game::new(list.clone(), History::new(rng_seed, 3), settings.clone(), 12345).unwrap();
+            let answer = game.strategy.committed_answer().unwrap().to_string();
+            game.hint();
+            game.hint();
+            game.guess('A');
+            game.guess('E');
+            play_out(&mut game);
+            assert_eq!(game.outcome().unwrap().answer, answer, "seed {}", rng_seed);
+        }
+    }
+
+    #[test]
+    fn the_same_seed_and_guesses_lose_on_the_same_word() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PRIZE", "QUOTE", "STONE", "PLANE"]);
+        let settings = Settings::builder().max_wrong_guesses(2).build().unwrap();
+        let lose = || {
+            let mut game = Game::new(list.clone(), History::new(11, 5), settings.clone(), 99).unwrap();
+            for c in ['X', 'Y', 'Z', 'J'] {
+                if game.is_over() { break; }
+                game.guess(c);
+            }
+            game.outcome().unwrap()
+        };
+        let (first, second) = (lose(), lose());
+        assert!(!first.won && !first.forfeited);
+        assert_eq!(first.answer, second.answer);
+        assert!(list.contains(&first.answer));
+    }
+
+    #[test]
+    fn fair_games_end_on_their_committed_answer() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PRIZE", "QUOTE", "STONE", "PLANE"]);
+        let settings = Settings::builder().strategy(Strategy::Fair).max_wrong_guesses(1).build().unwrap();
+        for rng_seed in 0..10 {
+            let mut game = Game::new(list.clone(), History::new(rng_seed, 5), settings.clone(), 99).unwrap();
+            let answer = game.strategy.committed_answer().unwrap().to_string();
+            assert_eq!(game.give_up(), answer);
+
+            let mut game = Game::new(list.clone(), History::new(rng_seed, 5), settings.clone(), 99).unwrap();
+            game.guess('X');
+            assert!(game.is_lost());
+            assert_eq!(game.outcome().unwrap().answer, answer, "seed {}", rng_seed);
+        }
+    }
+
+    // Count mode keeps the places hidden until every letter's been found, which is the win.
+    #[test]
+    fn count_mode_is_won_once_every_letter_is_found() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU"]);
+        let settings = Settings::builder().reveal_mode(RevealMode::Count).build().unwrap();
+        let mut game = Game::new(list, History::new(4, 3), settings, 0).unwrap();
+        let mut found = 0;
+        for c in 'A'..='Z' {
+            if game.is_over() { break; }
+            found += game.guess(c).newly_revealed;
+            if !game.is_won() { assert_eq!(game.shown(), "___"); }
+        }
+        assert!(game.is_won());
+        assert_eq!(found, 3);
+        assert_eq!(game.shown(), game.outcome().unwrap().answer);
+    }
+
+    // Games on one shared dictionary hold on to it rather than copying it, and each narrows
+    // only its own words, so what one does can't leak into the other.
+    #[test]
+    fn games_share_one_word_list() {
+        let list = Arc::new(words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "CRANE", "SLATE"]));
+        let mut first = Game::from_shared(Arc::clone(&list), History::new(1, 3), settings(), 0).unwrap();
+        let second = Game::from_shared(Arc::clone(&list), History::new(2, 5), settings(), 0).unwrap();
+        assert_eq!(Arc::strong_count(&list), 3);
+        assert!(Arc::ptr_eq(&first.words, &list));
+
+        play_out(&mut first);
+        assert!(first.is_over());
+        assert_eq!(second.word_list(), ["CRANE", "SLATE"]);
+        assert_eq!(second.remaining(), 2);
+        assert_eq!(list.len(), 8);
+        // and a shared game plays just like one on its own copy
+        let mut copied = Game::new(list.to_vec(), History::new(1, 3), settings(), 0).unwrap();
+        play_out(&mut copied);
+        assert_eq!(copied.outcome().unwrap().answer, first.outcome().unwrap().answer);
+        assert_eq!(copied.history().guesses, first.history().guesses);
+    }
+
+    // Restricting impossible guesses looks at the buckets first, and the guess has to reuse
+    // them rather than go over the words a second time.
+    #[test]
+    fn restricted_guesses_bucket_the_words_once() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "YAK"]);
+        let settings = Settings::builder().strategy(Strategy::Fair).build().unwrap();
+        let (looked, guessed, survivors) = on_one_thread(|| {
+            let mut game = Game::new(list, History::new(3, 3), settings, 0).unwrap();
+            let start = signatures();
+            assert!(!game.buckets('O').keys().eq([&GuessSignature::empty()]));
+            let looked = signatures() - start;
+            let result = game.guess('O');
+            (looked, signatures() - start - looked, result.remaining)
+        });
+        assert_eq!(looked, 8);
+        // only the debug check on the chosen bucket, never the whole list again
+        assert_eq!(guessed, if cfg!(debug_assertions) { survivors } else { 0 });
+    }
+
+    // Replaying a saved game narrows the words guess by guess, so it costs no more than
+    // playing those guesses did, rather than a pass over the whole dictionary each.
+    #[test]
+    fn replaying_goes_over_only_the_words_left() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PLANT", "STOMP", "FJORD", "QUICK"]);
+        let (played, replayed) = on_one_thread(|| {
+            let mut game = Game::new(list.clone(), History::new(5, 5), settings(), 0).unwrap();
+            let start = signatures();
+            for c in ['E', 'A', 'T'] { game.guess(c); }
+            let played = signatures() - start;
+            let mut history = game.history().clone();
+            history.snapshot = None;
+
+            let start = signatures();
+            let resumed = Game::new(list, history, settings(), 0).unwrap();
+            assert_eq!(resumed.word_list(), game.word_list());
+            (played, signatures() - start)
+        });
+        assert_eq!(replayed, played);
+        assert!(played < 3 * 10, "{} signatures for 3 guesses", played);
+    }
+
+    // Whether the strategy goes by bucket sizes or needs the buckets' words, a turn goes
+    // over the words left just once, and what it reports comes from that same pass.
+    #[test]
+    fn every_turn_signs_the_words_left_once() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PLANT", "STOMP", "FJORD", "QUICK"]);
+        for strategy in [Strategy::Classic, Strategy::Fair] {
+            let settings = Settings::builder().strategy(strategy).build().unwrap();
+            let (bucketed, per_turn) = on_one_thread(|| {
+                let mut game = Game::new(list.clone(), History::new(8, 5), settings, 0).unwrap();
+                let mut per_turn = vec![];
+                for c in ['E', 'R', 'A', 'T'] {
+                    let before = game.remaining();
+                    let start = signatures();
+                    let result = game.guess(c);
+                    assert_eq!(result.bucket_sizes.values().sum::<usize>(), before);
+                    per_turn.push((before, signatures() - start, result.remaining));
+                }
+                (game.strategy.by_size().is_none(), per_turn)
+            });
+            for (before, signed, left) in per_turn {
+                // the debug check in do_guess_bucketed goes over the chosen bucket again
+                let checked = if bucketed && cfg!(debug_assertions) { left } else { 0 };
+                assert_eq!(signed, before + checked, "{:?}", strategy);
+            }
+        }
+    }
+
+    // Gives back the same bits every time, so every dart lands in the same place.
+    struct Stuck(u64);
+
+    impl RngCore for Stuck {
+        fn next_u32(&mut self) -> u32 { self.0 as u32 }
+        fn next_u64(&mut self) -> u64 { self.0 }
+        fn fill_bytes(&mut self, dest: &mut [u8]) { dest.fill(self.0 as u8) }
+    }
+
+    // The weighted choice goes from the lightest bucket to the heaviest, so a dart at 0
+    // hits the single COT and one at the very end of the range hits the three without an O.
+    #[test]
+    fn a_fixed_rng_forces_the_bucket() {
+        let list = words(&["CAT", "PIG", "HEN", "COT"]);
+        let mut low = Game::with_rng(list.clone(), History::new(0, 3), settings(), Stuck(0)).unwrap();
+        low.guess('O');
+        assert_eq!(low.revealed(), "_O_");
+        assert_eq!(low.word_list(), ["COT"]);
+        let mut high = Game::with_rng(list, History::new(0, 3), settings(), Stuck(u64::MAX)).unwrap();
+        high.guess('O');
+        assert_eq!(high.revealed(), "___");
+        assert_eq!(high.remaining(), 3);
+    }
+
+    #[test]
+    fn another_blank_reveals_and_wins_like_the_underscore() {
+        let dotted = Settings { blank_char: Some('•'), ..settings() };
+        let mut game = Game::new(words(&["CAT", "COT"]), History::new(0, 3), dotted.clone(), 0).unwrap();
+        assert_eq!(game.revealed(), "•••");
+        game.guess('C');
+        assert_eq!(game.revealed(), "C••");
+        assert!(!game.is_won());
+        play_out(&mut game);
+        assert!(game.is_won());
+        assert!(!game.revealed().contains('•'));
+
+        // a snapshot taken with underscores can't be read with dots, so the guesses get replayed
+        let mut underscored = Game::new(words(&["CAT", "COT"]), History::new(0, 3), settings(), 0).unwrap();
+        underscored.guess('C');
+        let resumed = Game::new(words(&["CAT", "COT"]), underscored.history_with_snapshot(), dotted, 0).unwrap();
+        assert_eq!(resumed.revealed(), "C••");
+    }
+
+    // Checked by hand too, so this holds in release builds where the debug_assert isn't there.
+    #[test]
+    fn the_words_left_always_fit_what_is_revealed() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE", "PLANE", "STORE", "SHINE", "CREEK"]);
+        for strategy in [Strategy::Weighted, Strategy::Classic, Strategy::Fair, Strategy::Minimax] {
+            let settings = Settings::builder().strategy(strategy).build().unwrap();
+            let mut game = Game::new(list.clone(), History::new(11, 5), settings, 0).unwrap();
+            for guess in "ETAOINSHRCLKGBP".chars() {
+                if game.is_over() { break; }
+                let before = game.revealed().to_string();
+                game.guess(guess);
+                for word in game.word_list() {
+                    let sig = hangman::word_signature(word, guess);
+                    assert_eq!(hangman::display_signature(sig, &before, guess), game.revealed(), "{:?}: {} after {}", strategy, word, guess);
+                }
+                assert!(game.words_fit_revealed(), "{:?} after {}", strategy, guess);
+            }
+        }
+    }
+
+    #[test]
+    fn a_game_runs_on_built_settings() {
+        let settings = Settings::builder()
+            .strategy(Strategy::Classic)
+            .max_wrong_guesses(2)
+            .evil_exponent(3.0)
+            .history_path("elsewhere.toml")
+            .build()
+            .unwrap();
+        assert_eq!((settings.max_wrong_guesses, settings.evil_exponent, settings.history_path.as_str()), (Some(2), 3.0, "elsewhere.toml"));
+        let mut game = Game::new(words(&["CAT", "COT", "DOG", "PIG"]), History::new(0, 3), settings, 0).unwrap();
+        game.guess('Z');
+        game.guess('Q');
+        assert!(game.is_lost());
+        assert!(Settings::builder().evil_factor(0.0).build().is_err());
+    }
+
+    #[test]
+    fn giving_up_fills_in_a_word_that_fits() {
+        let mut game = Game::new(words(&["CAT", "DOG", "COW", "OWL", "EMU", "YAK", "PIG"]), History::new(1, 3), settings(), 0).unwrap();
+        game.guess('O');
+        let revealed = game.revealed().to_string();
+        let answer = game.give_up().to_string();
+        assert!(revealed.chars().zip(answer.chars()).all(|(r, a)| r == '_' || r == a), "{} after {}", answer, revealed);
+        assert_eq!(game.revealed(), answer);
+        assert!(game.is_over() && game.is_forfeited() && !game.is_won());
+        assert_eq!(game.history().status, GameStatus::Forfeit);
+        assert_eq!(game.history().score, Some(0));
+        let outcome = game.outcome().unwrap();
+        assert!(outcome.forfeited && !outcome.won);
+        assert_eq!(outcome.answer, answer);
+    }
+
+    // Words of other lengths don't come into it, since the game never sees them.
+    #[test]
+    fn resuming_against_a_changed_word_list_is_refused() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "OWL", "HORSE"]);
+        let mut game = Game::new(list.clone(), History::new(3, 3), settings(), 0).unwrap();
+        game.guess('E');
+        game.guess('O');
+        let saved = History { snapshot: None, ..game.history().clone() };
+        assert!(saved.word_list_hash.is_some());
+
+        let resumed = Game::new(list.clone(), saved.clone(), settings(), 0).unwrap();
+        assert_eq!(resumed.revealed(), game.revealed());
+        assert!(Game::new(words(&["CAT", "COT", "DOG", "PIG", "OWL", "GOOSE", "MOUSE"]), saved.clone(), settings(), 0).is_ok());
+        for changed in [words(&["CAT", "COT", "DOG", "PIG", "OWL", "YAK"]), words(&["CAT", "COT", "DOG", "PIG"])] {
+            assert!(matches!(Game::new(changed, saved.clone(), settings(), 0), Err(HangmanError::WordListChanged)));
+        }
+        // histories from before there was a hash take the list on trust
+        let old = History { word_list_hash: None, ..saved };
+        assert!(Game::new(words(&["CAT", "COT", "DOG", "PIG", "OWL", "YAK"]), old, settings(), 0).is_ok());
+    }
+}
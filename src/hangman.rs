@@ -1,124 +1,964 @@
 #![allow(unused)]
 
-use rand::{SeedableRng, Rng};
-use rand_chacha::ChaCha12Rng;
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::io;
-use std::io::Write;
-use std::collections::HashMap;
-use std::io::Take;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
 use std::iter;
-use std::slice::RChunksMut;
-use anyhow::{Result, Error};
+
+use crate::error::{HangmanError, Result};
+use crate::strategy::{ChoosesBySize, GuessStrategy, Strategy};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub evil_exponent: f64,
     pub evil_factor: f64,
-    pub word_list_path: String,
+    pub word_list_path: WordListPath,
     pub exclusions_list_path: String,
     pub salt_file_path: String,
     pub history_path: String,
+    // from before verbosity, and the same as a verbosity of 2; only read, never written
+    #[serde(default, skip_serializing)]
     pub verbose: bool,
+    // 0 is quiet, 1 shows where things stand after each move, 2 adds the statistics behind
+    // each guess and 3 the adversary's workings, which are logged at the info and debug levels.
+    // See verbosity_level for when it's left out.
+    #[serde(default)]
+    pub verbosity: Option<u8>,
+    #[serde(default)]
+    pub strategy: Strategy,
+    // None means unlimited, i.e. you can't lose
+    #[serde(default)]
+    pub max_wrong_guesses: Option<u32>,
+    // seed new games from today's date instead of asking
+    #[serde(default)]
+    pub daily: bool,
+    // weight words by the frequencies in the word list, if it has them
+    #[serde(default)]
+    pub use_frequencies: bool,
+    // a preset that overrides evil_exponent and evil_factor, and picks the word length for you
+    #[serde(default)]
+    pub difficulty: Option<Difficulty>,
+    // also save where the game had got to, so resuming doesn't replay every guess
+    #[serde(default)]
+    pub save_snapshots: bool,
+    // highlight new letters and the biggest bucket; never applies when output isn't a terminal
+    #[serde(default)]
+    pub color: bool,
+    // keeps several games going at once, each in its own history file; see history_file
+    #[serde(default)]
+    pub slot: Option<String>,
+    // write a move by move account of the game here once it's over (Markdown if it ends in .md)
+    #[serde(default)]
+    pub transcript_path: Option<String>,
+    // what a letter that's not been revealed yet shows as; None means '_'
+    #[serde(default)]
+    pub blank_char: Option<char>,
+    #[serde(default)]
+    pub output: OutputMode,
+    // how many buckets verbosity 2 and up lists after a guess, biggest first; 0 lists them all
+    #[serde(default = "default_stats_limit")]
+    pub stats_limit: usize,
+    // write a proof of the finished game here as JSON, salt and all, so it can be checked
+    #[serde(default)]
+    pub proof_path: Option<String>,
+    // refuse letters that no remaining word has, since they can only ever be a miss
+    #[serde(default)]
+    pub restrict_impossible: bool,
+    #[serde(default)]
+    pub reveal_mode: RevealMode,
+    // keep the seed out of the history file until the game's over, so it can't be read off
+    // to work out what the adversary will do. Resuming then asks for the seed again.
+    #[serde(default)]
+    pub hide_seed: bool,
+    // only play words this matches, e.g. "ING$". Words are uppercase by the time it sees them.
+    #[serde(default)]
+    pub word_filter_regex: Option<String>,
+    // turn accented letters into plain ones when loading, so "résumé" is played as RESUME
+    #[serde(default)]
+    pub normalize_accents: bool,
+    // only words made of these letters get played, and only they can be guessed, e.g. "ETAOINSHRDLU"
+    #[serde(default)]
+    pub allowed_letters: Option<String>,
+    // words that never get played, whatever the exclusions say; same format as the exclusions file
+    #[serde(default)]
+    pub blocklist_path: Option<String>,
+    // how many letters a new game starts with already filled in, like a crossword clue.
+    // Always leaves at least one to guess.
+    #[serde(default)]
+    pub reveal_hints: usize,
+    // with the weighted strategy, these ramp evil_exponent from the first value with no letters
+    // showing to the second with all of them; either left out is evil_exponent, or the other one
+    #[serde(default)]
+    pub evil_exponent_start: Option<f64>,
+    #[serde(default)]
+    pub evil_exponent_end: Option<f64>,
+    // with the weighted strategy, how much more it dislikes giving away letters in places
+    // few words have them; 0, the default, doesn't look at places at all
+    #[serde(default)]
+    pub evil_position_factor: f64,
+    // how many guesses ahead the minimax strategy looks, 1 or 2
+    #[serde(default = "default_minimax_depth")]
+    pub minimax_depth: u32,
+    // only play words from this category of the word lists, which is given as a clue, or
+    // "random" for a different one each game; see loading::Categories
+    #[serde(default)]
+    pub category: Option<String>,
+    // "WORD\tdefinition" lines, one of which gets shown when you win with that word
+    #[serde(default)]
+    pub definitions_path: Option<String>,
+    // what the game says to the player, to translate it or reword it; any left out are English
+    #[serde(default)]
+    pub prompts: Prompts,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_stats_limit() -> usize {
+    10
+}
+
+fn default_minimax_depth() -> u32 {
+    2
+}
+
+// What `init` writes out for a first run. The paths are placeholders to point at real files.
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            evil_exponent: 2.0,
+            evil_factor: 1.5,
+            word_list_path: WordListPath::One("words.txt".to_string()),
+            exclusions_list_path: "exclusions.txt".to_string(),
+            salt_file_path: "salts/salt.toml".to_string(),
+            history_path: "histories/history.toml".to_string(),
+            verbose: false,
+            verbosity: None,
+            strategy: Strategy::default(),
+            max_wrong_guesses: None,
+            daily: false,
+            use_frequencies: false,
+            difficulty: None,
+            save_snapshots: false,
+            color: false,
+            slot: None,
+            transcript_path: None,
+            blank_char: None,
+            output: OutputMode::default(),
+            stats_limit: default_stats_limit(),
+            proof_path: None,
+            restrict_impossible: false,
+            reveal_mode: RevealMode::default(),
+            hide_seed: false,
+            word_filter_regex: None,
+            normalize_accents: false,
+            allowed_letters: None,
+            blocklist_path: None,
+            reveal_hints: 0,
+            evil_exponent_start: None,
+            evil_exponent_end: None,
+            evil_position_factor: 0.0,
+            minimax_depth: default_minimax_depth(),
+            category: None,
+            definitions_path: None,
+            prompts: Prompts::default(),
+        }
+    }
+}
+
+// For setting up games from code: start from the defaults and change what you need, e.g.
+// Settings::builder().word_list_path("words.txt").max_wrong_guesses(6).build()
+#[derive(Debug, Clone, Default)]
+pub struct SettingsBuilder {
+    settings: Settings,
+}
+
+// One chainable setter per field. Optional fields take the value itself, not an Option.
+macro_rules! setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(pub fn $field(mut self, value: $ty) -> Self {
+            self.settings.$field = value;
+            self
+        })*
+    };
+    (some $($field:ident: $ty:ty),* $(,)?) => {
+        $(pub fn $field(mut self, value: $ty) -> Self {
+            self.settings.$field = Some(value);
+            self
+        })*
+    };
+}
+
+impl SettingsBuilder {
+    setters! {
+        evil_exponent: f64,
+        evil_factor: f64,
+        verbose: bool,
+        strategy: Strategy,
+        daily: bool,
+        use_frequencies: bool,
+        save_snapshots: bool,
+        color: bool,
+        output: OutputMode,
+        stats_limit: usize,
+        restrict_impossible: bool,
+        reveal_mode: RevealMode,
+        hide_seed: bool,
+        normalize_accents: bool,
+        reveal_hints: usize,
+        evil_position_factor: f64,
+        minimax_depth: u32,
+        prompts: Prompts,
+    }
+    setters! { some
+        max_wrong_guesses: u32,
+        difficulty: Difficulty,
+        blank_char: char,
+        verbosity: u8,
+        evil_exponent_start: f64,
+        evil_exponent_end: f64,
+    }
+
+    pub fn word_list_path(mut self, path: impl Into<String>) -> Self { self.settings.word_list_path = WordListPath::One(path.into()); self }
+    pub fn word_list_paths(mut self, paths: Vec<String>) -> Self { self.settings.word_list_path = WordListPath::Many(paths); self }
+    pub fn exclusions_list_path(mut self, path: impl Into<String>) -> Self { self.settings.exclusions_list_path = path.into(); self }
+    pub fn salt_file_path(mut self, path: impl Into<String>) -> Self { self.settings.salt_file_path = path.into(); self }
+    pub fn history_path(mut self, path: impl Into<String>) -> Self { self.settings.history_path = path.into(); self }
+    pub fn slot(mut self, slot: impl Into<String>) -> Self { self.settings.slot = Some(slot.into()); self }
+    pub fn transcript_path(mut self, path: impl Into<String>) -> Self { self.settings.transcript_path = Some(path.into()); self }
+    pub fn proof_path(mut self, path: impl Into<String>) -> Self { self.settings.proof_path = Some(path.into()); self }
+    pub fn word_filter_regex(mut self, pattern: impl Into<String>) -> Self { self.settings.word_filter_regex = Some(pattern.into()); self }
+    pub fn category(mut self, category: impl Into<String>) -> Self { self.settings.category = Some(category.into()); self }
+    pub fn definitions_path(mut self, path: impl Into<String>) -> Self { self.settings.definitions_path = Some(path.into()); self }
+    pub fn allowed_letters(mut self, letters: impl Into<String>) -> Self { self.settings.allowed_letters = Some(letters.into()); self }
+    pub fn blocklist_path(mut self, path: impl Into<String>) -> Self { self.settings.blocklist_path = Some(path.into()); self }
+
+    // Checked the same way a settings file is.
+    pub fn build(self) -> Result<Settings> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
+
+impl Settings {
+    pub fn builder() -> SettingsBuilder {
+        SettingsBuilder::default()
+    }
+
+    // verbosity if it's given, and otherwise 2 for an old verbose = true or 1 without.
+    pub fn verbosity_level(&self) -> u8 {
+        self.verbosity.unwrap_or(if self.verbose { 2 } else { 1 })
+    }
+
+    // Swap in the difficulty preset's evil parameters, if there is one.
+    pub fn apply_difficulty(&mut self) {
+        if let Some(difficulty) = self.difficulty {
+            let (evil_exponent, evil_factor, _) = difficulty.parameters();
+            self.evil_exponent = evil_exponent;
+            self.evil_factor = evil_factor;
+        }
+    }
+
+    pub fn blank(&self) -> char {
+        self.blank_char.unwrap_or('_')
+    }
+
+    // The category every game is in, uppercased like the word lists' ones, unless it's random.
+    pub fn fixed_category(&self) -> Option<String> {
+        self.category.as_ref().filter(|_| !self.random_category()).map(|name| name.trim().to_uppercase())
+    }
+
+    pub fn random_category(&self) -> bool {
+        self.category.as_ref().is_some_and(|name| name.trim().eq_ignore_ascii_case("random"))
+    }
+
+    // Whether allowed_letters lets this letter be played, in either case.
+    pub fn allows(&self, letter: char) -> bool {
+        self.allowed_letters.as_ref().is_none_or(|letters| letters.to_uppercase().contains(letter.to_ascii_uppercase()))
+    }
+
+    // Catches values that would otherwise only blow up mid-game, naming the field at fault.
+    // A negative evil_exponent is allowed: it makes small buckets likelier, which is odd but not broken.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.evil_factor.is_finite() && self.evil_factor > 0.0) {
+            return Err(HangmanError::InvalidSettings(format!("evil_factor must be a positive number, not {}", self.evil_factor)));
+        }
+        if !self.evil_exponent.is_finite() {
+            return Err(HangmanError::InvalidSettings(format!("evil_exponent must be a finite number, not {}", self.evil_exponent)));
+        }
+        for (field, exponent) in [("evil_exponent_start", self.evil_exponent_start), ("evil_exponent_end", self.evil_exponent_end)] {
+            if let Some(exponent) = exponent
+                && !exponent.is_finite() {
+                return Err(HangmanError::InvalidSettings(format!("{} must be a finite number, not {}", field, exponent)));
+            }
+        }
+        if !(self.evil_position_factor.is_finite() && self.evil_position_factor >= 0.0) {
+            return Err(HangmanError::InvalidSettings(format!("evil_position_factor must be 0 or more, not {}", self.evil_position_factor)));
+        }
+        if self.word_list_path.paths().is_empty() {
+            return Err(HangmanError::InvalidSettings("word_list_path needs at least one file in it".to_string()));
+        }
+        let word_list_fields = self.word_list_path.paths().iter().map(|path| ("word_list_path", path));
+        for (field, path) in word_list_fields.chain([
+            ("exclusions_list_path", &self.exclusions_list_path),
+            ("salt_file_path", &self.salt_file_path),
+            ("history_path", &self.history_path),
+        ]) {
+            if path.trim().is_empty() {
+                return Err(HangmanError::InvalidSettings(format!("{} can't be empty", field)));
+            }
+        }
+        if self.blank().is_alphabetic() {
+            return Err(HangmanError::InvalidSettings(format!("blank_char {:?} can't be a letter, or it'd be mistaken for one", self.blank())));
+        }
+        if let Some(letters) = &self.allowed_letters
+            && (letters.is_empty() || !letters.chars().all(|c| c.is_alphabetic())) {
+            return Err(HangmanError::InvalidSettings(format!("allowed_letters {:?} has to be one or more letters and nothing else", letters)));
+        }
+        if self.category.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            return Err(HangmanError::InvalidSettings("category can't be empty".to_string()));
+        }
+        if self.verbosity_level() > 3 {
+            return Err(HangmanError::InvalidSettings(format!("verbosity goes from 0 to 3, not {}", self.verbosity_level())));
+        }
+        if !(1..=2).contains(&self.minimax_depth) {
+            return Err(HangmanError::InvalidSettings(format!("minimax_depth has to be 1 or 2, not {}; any deeper takes far too long", self.minimax_depth)));
+        }
+        if self.max_wrong_guesses == Some(0) {
+            return Err(HangmanError::InvalidSettings("max_wrong_guesses has to be at least 1, or left out for unlimited".to_string()));
+        }
+        if let Some(slot) = &self.slot
+            && (slot.is_empty() || slot.contains(['/', '\\'])) {
+            return Err(HangmanError::InvalidSettings(format!("slot {:?} has to be a plain name, without any slashes", slot)));
+        }
+        Ok(())
+    }
+
+    // Where this game's history lives. With a slot, its name goes before the extension,
+    // so slot 3 of "histories/h.toml" is "histories/h.3.toml".
+    pub fn history_file(&self) -> String {
+        let Some(slot) = &self.slot else { return self.history_path.clone() };
+        let path = Path::new(&self.history_path);
+        let stem = path.file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        let name = match path.extension() {
+            Some(ext) => format!("{}.{}.{}", stem, slot, ext.to_string_lossy()),
+            None => format!("{}.{}", stem, slot),
+        };
+        path.with_file_name(name).to_string_lossy().into_owned()
+    }
+}
+
+// A settings file can give one word list, or a list of them to merge.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WordListPath {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl WordListPath {
+    pub fn paths(&self) -> &[String] {
+        match self {
+            WordListPath::One(path) => std::slice::from_ref(path),
+            WordListPath::Many(paths) => paths,
+        }
+    }
+}
+
+// What a letter guess tells the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevealMode {
+    // where the letter is, the usual way
+    #[default]
+    Positions,
+    // only how many times it's in the word. The adversary still settles on positions
+    // behind the scenes, so the word is done once every letter in it has been counted.
+    Count,
+}
+
+// How the command line game reports what's going on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    // text for a person
+    #[default]
+    Human,
+    // one JSON object per move on stdout, for other programs; the text goes to stderr instead
+    Ndjson,
+}
+
+// The questions the command line game asks and what it says at the end, in a [prompts]
+// table of the settings file. In the end of game ones, {} is where the word goes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prompts {
+    pub seed: String,
+    pub letter_count: String,
+    pub next_guess: String,
+    // when what was typed makes no sense
+    pub try_again: String,
+    pub won: String,
+    pub gave_up: String,
+    pub lost: String,
+}
+
+impl Default for Prompts {
+    fn default() -> Self {
+        Prompts {
+            seed: "Random seed? ".to_string(),
+            letter_count: "Number of letters? ".to_string(),
+            next_guess: "Next guess? ".to_string(),
+            try_again: "I couldn't read that, try again.".to_string(),
+            won: "Winner! The word was {}".to_string(),
+            gave_up: "You gave up. The word was {}".to_string(),
+            lost: "You lose! The word was {}".to_string(),
+        }
+    }
+}
+
+impl Prompts {
+    // One of the end of game prompts with the word put in, or tacked on if there's no {}.
+    pub fn with_word(prompt: &str, word: &str) -> String {
+        if prompt.contains("{}") { prompt.replacen("{}", word, 1) } else { format!("{} {}", prompt, word) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    // (evil_exponent, evil_factor, word lengths to pick from)
+    // Long words are easier since more letters reveal something. Easy barely prefers big buckets
+    // and is happy to reveal letters; Hard's steep exponent almost always keeps the biggest
+    // bucket, which is nearly classic evil hangman.
+    pub fn parameters(self) -> (f64, f64, RangeInclusive<usize>) {
+        match self {
+            Difficulty::Easy => (0.5, 0.8, 8..=12),
+            Difficulty::Medium => (1.0, 1.25, 6..=9),
+            Difficulty::Hard => (4.0, 2.0, 4..=6),
+        }
+    }
+}
+
+// Bumped whenever History changes in a way older files need migrating for. Version 2 added
+// snapshots, seed phrases, timing and status, version 3 hidden seeds and version 4 giving
+// up, and version 5 letters revealed from the start; files without a version are version 1.
+pub const HISTORY_VERSION: u32 = 5;
+
+fn first_version() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct History {
+    #[serde(default = "first_version")]
+    pub version: u32,
     pub rng_seed: u64,
     pub letter_count: usize,
-    pub guesses: Vec<char>,
+    pub guesses: Vec<Guess>,
+    // guesses that revealed nothing
+    #[serde(default)]
+    pub wrong_count: u32,
+    // only filled in once the game is over
+    #[serde(default)]
+    pub score: Option<i64>,
+    // only there if settings.save_snapshots was on
+    #[serde(default)]
+    pub snapshot: Option<Snapshot>,
+    // what was typed for the seed, if it was a phrase rather than a number, just for showing
+    #[serde(default)]
+    pub seed_phrase: Option<String>,
+    // when the game began, in seconds since the epoch
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    // time spent actually playing, over every session, as of the last save
+    #[serde(default)]
+    pub elapsed_seconds: u64,
+    #[serde(default)]
+    pub status: GameStatus,
+    // with settings.hide_seed, proof::commitment of the seed. While the game's in progress
+    // it's saved in place of the seed, which only gets written out once the game is over.
+    #[serde(default)]
+    pub seed_commitment: Option<u64>,
+    // the letters settings.reveal_hints gave away before the first guess, kept so resuming
+    // doesn't depend on the setting staying the same
+    #[serde(default)]
+    pub pre_revealed: Vec<Revealed>,
+    // what the word was, once the game's over; the same as GameOutcome's answer
+    #[serde(default)]
+    pub answer: Option<String>,
+    // seed::word_list_hash of the length-filtered dictionary the game started from, so it
+    // can't be carried on against different words, which would make the replay go differently.
+    // None until the game first starts, and in files from before it was kept.
+    #[serde(default)]
+    pub word_list_hash: Option<u64>,
+    // the category of the word lists the words came from, shown as a clue
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+// A letter shown from the start of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Revealed {
+    // counting from 0 at the start of the word
+    pub position: usize,
+    pub letter: char,
 }
 
 impl History {
-    pub fn write_to_file(&self, path: &str) {
-        let history_string = toml::to_string_pretty(self)
-            .expect("Failed to serialize history");
-        fs::write(path, history_string)
-            .expect("Failed to write history file");
+    pub fn new(rng_seed: u64, letter_count: usize) -> Self {
+        History { version: HISTORY_VERSION, rng_seed, letter_count, guesses: Vec::new(), wrong_count: 0, score: None, snapshot: None, seed_phrase: None, started_at: None, elapsed_seconds: 0, status: GameStatus::InProgress, seed_commitment: None, pre_revealed: Vec::new(), answer: None, word_list_hash: None, category: None }
+    }
+
+    // Brings a history from an older version up to this one. Errors on one from a newer
+    // version, since there's no telling what it has in it that we'd quietly drop.
+    pub fn migrate(mut self) -> Result<Self> {
+        if self.version > HISTORY_VERSION {
+            return Err(HangmanError::UnsupportedVersion { found: self.version, supported: HISTORY_VERSION });
+        }
+        // 1 to 2: everything new has a default that suits an old file. Even a finished
+        // version 1 game is fine as in progress, since replaying it shows it's over.
+        // 2 to 3: no hidden seed is what version 2 always had. The bump is so a version 2
+        // binary refuses a hidden seed file instead of playing it with a seed of 0.
+        // 3 to 4: likewise, so a version 3 binary says why it can't read a "*" guess.
+        // 4 to 5: and so a version 4 binary doesn't quietly drop the pre-revealed letters.
+        self.version = HISTORY_VERSION;
+        Ok(self)
     }
+
+    // Just the letters, leaving out hints and the like.
+    pub fn guessed_letters(&self) -> Vec<char> {
+        self.guesses.iter().filter_map(|g| match g {
+            Guess::Letter(c) => Some(*c),
+            Guess::Hint | Guess::Word(_) | Guess::GiveUp => None,
+        }).collect()
+    }
+}
+
+// Whether a saved game is finished. Files from before this existed count as in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameStatus {
+    #[default]
+    InProgress,
+    Won,
+    Lost,
+    // the player gave up
+    Forfeit,
 }
 
+// Where a game had got to after some guesses, so it can pick up from there instead of
+// replaying them all against the whole dictionary. Only trusted if the dictionary hasn't
+// changed since, which is what word_list_hash is for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    // seed::word_list_hash of the length-filtered dictionary the game started from
+    pub word_list_hash: u64,
+    // how many of the history's guesses this accounts for
+    pub guess_count: usize,
+    pub word_info: String,
+    pub rng_word_pos: String,  // a u128, which TOML can't hold as a number
+    pub words: Vec<String>,
+}
+
+// One move by the player. These go in the history file as plain strings
+// ("A", "?" for a hint, or a whole word) so files from before hints existed still load.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Guess {
+    Letter(char),
+    Hint,
+    // trying to solve it outright
+    Word(String),
+    // conceding, which ends the game
+    GiveUp,
+}
+
+impl fmt::Display for Guess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Guess::Letter(c) => write!(f, "{}", c),
+            Guess::Hint => write!(f, "?"),
+            Guess::GiveUp => write!(f, "*"),
+            Guess::Word(w) => write!(f, "{}", w),
+        }
+    }
+}
+
+impl From<Guess> for String {
+    fn from(guess: Guess) -> String {
+        guess.to_string()
+    }
+}
+
+impl TryFrom<String> for Guess {
+    type Error = HangmanError;
+
+    fn try_from(s: String) -> Result<Self> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some('?'), None) => Ok(Guess::Hint),
+            (Some('*'), None) => Ok(Guess::GiveUp),
+            (Some(c), None) if c.is_alphabetic() => Ok(Guess::Letter(c.to_ascii_uppercase())),
+            (Some(_), Some(_)) if s.chars().all(char::is_alphabetic) => Ok(Guess::Word(s.to_uppercase())),
+            _ => Err(HangmanError::InvalidGuess(format!("Unrecognized guess {:?}", s))),
+        }
+    }
+}
+
+// How common each word is, from a word list with frequencies in it.
+// Words that aren't in here count as 1.0.
+pub type WordWeights = HashMap<String, f64>;
+
 // Track which letters in a word match the guess using bit flags
 // The least significant bit corresponds to the start of the word.
-type GuessSignature = u64;
+// u128 so that the occasional monster medical term still fits. It's a type of its own
+// so a letter count or a word index can't be passed off as one; the derived Ord is the
+// u128's, which is what sorting buckets relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub struct GuessSignature(u128);
+
+impl GuessSignature {
+    pub const BITS: u32 = u128::BITS;
+
+    // For building one by hand, e.g. from_bits(0b1100) for the third and fourth letters.
+    // Bits past the end of the word are never looked at.
+    pub fn from_bits(bits: u128) -> Self {
+        GuessSignature(bits)
+    }
+
+    pub fn bits(self) -> u128 {
+        self.0
+    }
+
+    // No letters matched, i.e. a miss.
+    pub fn empty() -> Self {
+        GuessSignature(0)
+    }
+
+    // Every one of num_letters positions matched.
+    pub fn full(num_letters: usize) -> Self {
+        if num_letters == 0 { Self::empty() } else { GuessSignature(u128::MAX >> (MAX_LETTERS - num_letters)) }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn matches_count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// Whether each of the first num_letters positions matched, the first letter first.
+    ///
+    /// ```
+    /// use manifold_hangman::hangman::GuessSignature;
+    ///
+    /// let bits: Vec<bool> = GuessSignature::from_bits(0b0110).decode(5).collect();
+    /// assert_eq!(bits, [false, true, true, false, false]);
+    /// ```
+    pub fn decode(self, num_letters: usize) -> SignatureBits {
+        SignatureBits { sig: self.0, front: 0, back: num_letters }
+    }
+}
+
+// The positions of a signature, one bool each, from GuessSignature::decode. Works from
+// either end, so .rev() goes from the last letter back, and always knows its len().
+#[derive(Debug, Clone)]
+pub struct SignatureBits {
+    sig: u128,
+    // positions still to go are front..back
+    front: usize,
+    back: usize,
+}
+
+impl SignatureBits {
+    fn bit(&self, position: usize) -> bool {
+        (self.sig >> position) & 1 == 1
+    }
+}
+
+impl Iterator for SignatureBits {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.front == self.back { return None; }
+        self.front += 1;
+        Some(self.bit(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for SignatureBits {
+    fn next_back(&mut self) -> Option<bool> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        Some(self.bit(self.back))
+    }
+}
+
+impl ExactSizeIterator for SignatureBits {}
+
+impl iter::FusedIterator for SignatureBits {}
+
+// For debugging, where the bits are easier to read than the number.
+impl fmt::Binary for GuessSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Binary::fmt(&self.0, f)
+    }
+}
+
+// One bit per letter, so this is the longest word a signature can describe.
+pub const MAX_LETTERS: usize = GuessSignature::BITS as usize;
+
+// Length in letters rather than bytes, so accented words don't get miscounted.
+pub fn letter_len(word: &str) -> usize {
+    word.chars().count()
+}
+
+// How many words there are of each length, shortest first. Lengths with no words aren't in it.
+pub fn length_histogram(word_list: &[String]) -> BTreeMap<usize, usize> {
+    let mut counts = BTreeMap::new();
+    for word in word_list {
+        *counts.entry(letter_len(word)).or_insert(0) += 1;
+    }
+    counts
+}
+
+// What the word list accepts: at least one letter, and only A-Z in either case.
+pub fn is_all_ascii_alpha(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|c| c.is_ascii_alphabetic())
+}
 
-// Checks which letters of a word match a guess letter and stores it as a signature.
-fn word_signature(word: &String, guess: char) -> GuessSignature {
-    let mut sig: GuessSignature = 0;
+/// Which letters of a word match the guess, as a signature with the first letter in the
+/// lowest bit.
+///
+/// ```
+/// use manifold_hangman::hangman::{word_signature, GuessSignature};
+///
+/// assert_eq!(word_signature("BANANA", 'A'), GuessSignature::from_bits(0b101010));
+/// // only the first letter, so only the lowest bit
+/// assert_eq!(word_signature("BANANA", 'B').bits(), 1);
+/// assert!(word_signature("BANANA", 'Z').is_empty());
+/// ```
+pub fn word_signature(word: &str, guess: char) -> GuessSignature {
+    #[cfg(test)]
+    tests::signature_taken();
+    let mut sig: u128 = 0;
     for letter in word.chars().rev() {  // the rev() here saves me a headache
         sig *= 2;
         if letter == guess {sig += 1;}
     }
-    sig
+    GuessSignature(sig)
 }
 
-// returns an enum of bools indicating whether the signature matches the guess, 
-// with the first bool returned referring to the first letter of the word.
-fn decode_signature(mut sig: GuessSignature, num_letters: usize) -> impl Iterator<Item=bool> {
-    let mut count = 0;
-    return iter::from_fn(move || {
-        if count == num_letters {
-            None
-        } else {
-            count += 1;
-            let m = sig % 2;    // no need to worry about signs because it's u64s 
-            sig = (sig - m)/2;
-            Some(m==1)
-        }
-    })
+/// Fills the guess into prev_info, the word as shown before the guess, wherever the
+/// signature says it matched.
+///
+/// ```
+/// use manifold_hangman::hangman::{display_signature, word_signature, GuessSignature};
+///
+/// // bits 2 and 3 are the third and fourth letters, counting the lowest bit as the first
+/// assert_eq!(display_signature(GuessSignature::from_bits(0b1100), "_A___T", 'B'), "_ABB_T");
+/// assert_eq!(display_signature(GuessSignature::from_bits(0b000001), "_A___T", 'B'), "BA___T");
+/// // and it undoes word_signature
+/// assert_eq!(display_signature(word_signature("RABBIT", 'B'), "______", 'B'), "__BB__");
+/// ```
+pub fn display_signature(sig: GuessSignature, prev_info: &str, guess: char) -> String {
+    let num_letters = letter_len(prev_info);
+    sig.decode(num_letters).zip(prev_info.chars()).map(|(b, c)| if b {guess} else {c}).collect()
 }
 
-// prev_info is the information from before the guess, which should be updated by the guess signature.
-// e.g. display_signature(12, "_A___T", 'B') = "_ABB_T"
-fn display_signature(sig: GuessSignature, prev_info: &String, guess: char) -> String {
-    let num_letters = prev_info.len();
-    decode_signature(sig, num_letters).zip(prev_info.chars()).map(|(b, c)| if b {guess} else {c}).collect()
+// Words split up by the signature a guess gives them. Rather than copies of the words,
+// each bucket holds indices into the word list the buckets were made from.
+pub type Buckets = HashMap<GuessSignature, Vec<usize>>;
+
+// Just how many words each bucket would have.
+pub type BucketSizes = HashMap<GuessSignature, usize>;
+
+// This is the part that does the real work. Sorts the word list into buckets based on guess signature.
+#[cfg(not(feature = "parallel"))]
+pub fn guess_buckets(word_list: &[String], guess: char) -> Buckets {
+    guess_buckets_sequential(word_list, guess)
 }
 
-fn count_matches(sig: GuessSignature) -> u32 {
-    sig.count_ones()
+// Same as the sequential version, just spread over threads. Each thread buckets its own
+// chunk and the maps get merged at the end.
+#[cfg(feature = "parallel")]
+pub fn guess_buckets(word_list: &[String], guess: char) -> Buckets {
+    use rayon::prelude::*;
+
+    word_list.par_iter().enumerate()
+        .fold(HashMap::new, |mut buckets: Buckets, (i, word)| {
+            buckets.entry(word_signature(word, guess)).or_default().push(i);
+            buckets
+        })
+        .reduce(HashMap::new, |mut left, right| {
+            for (sig, indices) in right {
+                left.entry(sig).or_default().extend(indices);
+            }
+            left
+        })
 }
 
-// This is the part that does the real work. Sorts the word list into buckets based on guess signature.
-// Clones the words in word_list.
-fn guess_buckets(word_list: &Vec<String>, guess: char) -> HashMap<GuessSignature, Vec<String>> {
-    let mut buckets: HashMap<GuessSignature, Vec<String>> = HashMap::new();
+// guess_buckets for just the words at the indices in among, which is what the buckets hold,
+// e.g. the words a game still has out of a dictionary it shares with others. Words keep
+// among's order within each bucket.
+#[cfg(not(feature = "parallel"))]
+pub fn guess_buckets_among(word_list: &[String], among: &[usize], guess: char) -> Buckets {
+    let mut buckets: Buckets = HashMap::new();
+    for &i in among {
+        buckets.entry(word_signature(&word_list[i], guess)).or_default().push(i);
+    }
+    buckets
+}
+
+#[cfg(feature = "parallel")]
+pub fn guess_buckets_among(word_list: &[String], among: &[usize], guess: char) -> Buckets {
+    use rayon::prelude::*;
+
+    among.par_iter()
+        .fold(HashMap::new, |mut buckets: Buckets, &i| {
+            buckets.entry(word_signature(&word_list[i], guess)).or_default().push(i);
+            buckets
+        })
+        .reduce(HashMap::new, |mut left, right| {
+            for (sig, indices) in right {
+                left.entry(sig).or_default().extend(indices);
+            }
+            left
+        })
+}
+
+pub fn guess_buckets_sequential(word_list: &[String], guess: char) -> Buckets {
+    let mut buckets: Buckets = HashMap::new();
     
-    for word in word_list.iter() {
+    for (i, word) in word_list.iter().enumerate() {
         let sig = word_signature(word, guess);
         if let Some(vec) = buckets.get_mut(&sig) {
-            vec.push(word.clone());
+            vec.push(i);
         } else {
-            buckets.insert(sig, vec![word.clone()]);
+            buckets.insert(sig, vec![i]);
         }
     }
 
     buckets
 }
 
-fn display_guess_statistics(buckets: &HashMap<GuessSignature, Vec<String>>, prev_info: &String, guess: char) {
-    let keys = buckets.keys();
-    let mut pairs: Vec<(usize, GuessSignature)> = keys.map(|sig| (buckets.get(sig).unwrap().len(), *sig)).collect();
+// guess_buckets without the words, only how many fall in each bucket.
+#[cfg(not(feature = "parallel"))]
+pub fn guess_bucket_sizes(word_list: &[impl AsRef<str>], guess: char) -> BucketSizes {
+    let mut sizes = HashMap::new();
+    for word in word_list {
+        *sizes.entry(word_signature(word.as_ref(), guess)).or_insert(0) += 1;
+    }
+    sizes
+}
+
+#[cfg(feature = "parallel")]
+pub fn guess_bucket_sizes(word_list: &[impl AsRef<str> + Sync], guess: char) -> BucketSizes {
+    use rayon::prelude::*;
+
+    word_list.par_iter()
+        .fold(HashMap::new, |mut sizes: BucketSizes, word| {
+            *sizes.entry(word_signature(word.as_ref(), guess)).or_insert(0) += 1;
+            sizes
+        })
+        .reduce(HashMap::new, |mut left, right| {
+            for (sig, n) in right {
+                *left.entry(sig).or_insert(0) += n;
+            }
+            left
+        })
+}
+
+// The signature of each word at the indices in among, in among's order: the one pass over
+// the words a guess needs when the buckets' words aren't wanted, since a bucket is then just
+// the indices with its signature.
+#[cfg(not(feature = "parallel"))]
+pub fn word_signatures(word_list: &[String], among: &[usize], guess: char) -> Vec<GuessSignature> {
+    among.iter().map(|&i| word_signature(&word_list[i], guess)).collect()
+}
 
-    // reverse sort to sort by word matches highest to lowest,
-    // then whatever reverse signature sort is - it just needs to be consistent
-    pairs.sort_by(|a, b| b.cmp(a));
+#[cfg(feature = "parallel")]
+pub fn word_signatures(word_list: &[String], among: &[usize], guess: char) -> Vec<GuessSignature> {
+    use rayon::prelude::*;
 
-    for (n, sig) in pairs {
-        println!("{}: {}", display_signature(sig, prev_info, guess), n);
+    among.par_iter().map(|&i| word_signature(&word_list[i], guess)).collect()
+}
+
+// How many of the words have each letter of A-Z in them somewhere, leaving out the ones
+// in already. Most common first, ties alphabetically, and letters no word has are left off.
+pub fn letter_frequencies(word_list: &[impl AsRef<str>], already: &[char]) -> Vec<(char, usize)> {
+    let mut counts = [0usize; 26];
+    for word in word_list {
+        let mut seen = [false; 26];
+        for c in word.as_ref().chars().filter(char::is_ascii_uppercase) {
+            seen[(c as u8 - b'A') as usize] = true;
+        }
+        for (count, seen) in counts.iter_mut().zip(seen) {
+            if seen { *count += 1; }
+        }
     }
+    let mut frequencies: Vec<(char, usize)> = ('A'..='Z').zip(counts)
+        .filter(|(c, n)| *n > 0 && !already.contains(c))
+        .collect();
+    frequencies.sort_by(|(c1, n1), (c2, n2)| n2.cmp(n1).then(c1.cmp(c2)));
+    frequencies
+}
+
+// How many words don't have the guess in them at all, i.e. the bucket an adversary
+// keeps to make the guess a miss. None if every word has it, so it can't be denied.
+pub fn zero_match_bucket(word_list: &[impl AsRef<str>], guess: char) -> Option<usize> {
+    let misses = word_list.iter().filter(|word| !word.as_ref().contains(guess)).count();
+    (misses > 0).then_some(misses)
+}
+
+// How evenly the guess splits the words, in bits: the Shannon entropy -Σ p log2 p of the
+// buckets, p being each one's share of the words. 0 when they'd all land in one bucket.
+// Sync only so the parallel guess_bucket_sizes can share the words out.
+pub fn guess_entropy(word_list: &[impl AsRef<str> + Sync], guess: char) -> f64 {
+    let total = word_list.len() as f64;
+    // summed in a fixed order, so ties between letters don't come and go with the hash order
+    let mut sizes: Vec<usize> = guess_bucket_sizes(word_list, guess).into_values().collect();
+    sizes.sort_unstable();
+    sizes.into_iter()
+        .map(|n| n as f64 / total)
+        .map(|p| p * (1.0 / p).log2())
+        .sum()
+}
+
+// The unguessed letter in the most remaining words, as advice for the player. This ignores the
+// adversary entirely, so it's a decent guess rather than a guaranteed good one.
+// None once every letter any word has is guessed.
+pub fn best_guess(word_list: &[impl AsRef<str>], already: &[char]) -> Option<char> {
+    letter_frequencies(word_list, already).first().map(|(c, _)| *c)
+}
+
+// Keep just the words at the given indices, moving rather than cloning them.
+pub fn retain_indices(word_list: &mut Vec<String>, keep: &[usize]) {
+    let mut kept = vec![false; word_list.len()];
+    for &i in keep { kept[i] = true; }
+    let mut kept = kept.into_iter();
+    word_list.retain(|_| kept.next().unwrap());
 }
 
 // there's a library for this but it works with u32 weights and I want f64 weights
-// we'll assume no NaN values or other problematic squirreliness. float rounding
-// will be an issue but I think this handles it credibly.
-// Assumes the option list is non-empty.
-fn weighted_choice<T: Ord + Clone, R: Rng>(options: &mut Vec<(f64, T)>, rng: &mut R) -> T {
-    // put these in a consistent order, lowest to highest weight.
-    // partially for repeatability, partially for numerical stability issues:
-    // if there's anything squirrely with the float math, we want to be subtracting
-    // small from large until the very end, and land in the biggest bucket if there are issues.
+// Picks an option with probability in proportion to its weight.
+// Returns None if the option list is empty or no weight is positive; see weighted_choice_index.
+pub fn weighted_choice<T: Ord + Clone, R: Rng + ?Sized>(options: &mut [(f64, T)], rng: &mut R) -> Option<T> {
+    // put these in a consistent order first (weighted_choice_index breaks ties by position,
+    // so ties among equal weights go by T) so we don't depend on whatever order they came in.
     options.sort_by(|(w1, x1), (w2, x2)| {
         match w1.total_cmp(w2) {
             std::cmp::Ordering::Equal => x1.cmp(x2),
@@ -126,151 +966,541 @@ fn weighted_choice<T: Ord + Clone, R: Rng>(options: &mut Vec<(f64, T)>, rng: &mu
         }
     }); // lexicographical order using total_cmp
 
-    let total_weight: f64 = options.iter().map(|t| t.0).sum();
+    let weights: Vec<f64> = options.iter().map(|(w, _)| *w).collect();
+    weighted_choice_index(&weights, rng).map(|i| options[i].1.clone())
+}
+
+// The same thing, but gives back which position got picked.
+// Weights that are NaN, infinite or negative count as zero. The evil weights can blow
+// up like that with extreme settings, and there's no sensible odds to give them.
+// float rounding will be an issue but I think this handles it credibly.
+// Returns None if weights is empty or none of them are positive.
+pub fn weighted_choice_index<R: Rng + ?Sized>(weights: &[f64], rng: &mut R) -> Option<usize> {
+    let weight = |i: usize| if weights[i].is_finite() && weights[i] > 0.0 { weights[i] } else { 0.0 };
+
+    // lowest to highest weight, ties by position.
+    // partially for repeatability, partially for numerical stability issues:
+    // if there's anything squirrely with the float math, we want to be subtracting
+    // small from large until the very end, and land in the biggest bucket if there are issues.
+    let mut order: Vec<usize> = (0..weights.len()).collect();
+    order.sort_by(|&i, &j| weight(i).total_cmp(&weight(j)).then(i.cmp(&j)));
+
+    // the heaviest option, if it has any weight at all
+    let heaviest = order.last().copied().filter(|&i| weight(i) > 0.0);
+
+    let total_weight: f64 = order.iter().map(|&i| weight(i)).sum();
+    // all finite, but a sum of huge ones can still overflow
+    if total_weight <= 0.0 || total_weight.is_infinite() {
+        return heaviest;
+    }
     let mut dart: f64 = rng.random::<f64>() * total_weight;
 
-    for (w, x) in options.iter() {
-        dart -= w;
-        if dart < 0.0 { return x.clone() }
+    for &i in &order {
+        dart -= weight(i);
+        if dart < 0.0 { return Some(i) }
     }
 
     //If we're still going, something squirrely happened with the float math and we should choose the most likely option
-    options.last().unwrap().1.clone()
+    heaviest
 }
 
-// Assumes the map of buckets is non-empty
-fn choose_guess_outcome<R: Rng>(buckets: &HashMap<GuessSignature, Vec<String>>, settings: &Settings, rng: &mut R) -> GuessSignature {
-    let mut options = Vec::new();
+// The strategies assume they're never handed an empty map, so this is the guard in front of them.
+// Returns None if there are no buckets to choose from, i.e. the word list was empty.
+// words is the list the buckets index into.
+pub fn choose_guess_outcome(buckets: &Buckets, words: &[String], strategy: &dyn GuessStrategy, rng: &mut dyn RngCore) -> Option<GuessSignature> {
+    if buckets.is_empty() {
+        None
+    } else {
+        Some(strategy.choose(buckets, words, rng))
+    }
+}
 
-    for (sig, bucket) in buckets {
-        let num_correct = count_matches(*sig);
-        let bucket_size = bucket.len();
-        let weight = (bucket_size as f64).powf(settings.evil_exponent) / settings.evil_factor.powf(num_correct as f64);
-        options.push((weight, *sig));
+// What do_guess decided.
+#[derive(Debug, Clone)]
+pub struct GuessOutcome {
+    // every bucket the guess split the words into, the chosen one included
+    pub buckets: Buckets,
+    pub chosen: GuessSignature,
+    // how many positions the chosen signature reveals; 0 is a miss
+    pub revealed_count: u32,
+    // the chosen bucket: indices of the words that are still possible
+    pub survivors: Vec<usize>,
+}
+
+pub fn do_guess(guess: char, word_list: &[String], strategy: &dyn GuessStrategy, rng: &mut dyn RngCore) -> Result<GuessOutcome> {
+    do_guess_bucketed(guess, guess_buckets(word_list, guess), word_list, strategy, rng)
+}
+
+// Same as do_guess, with the guess_buckets for it already worked out.
+pub fn do_guess_bucketed(guess: char, buckets: Buckets, word_list: &[String], strategy: &dyn GuessStrategy, rng: &mut dyn RngCore) -> Result<GuessOutcome> {
+    let chosen = choose_guess_outcome(&buckets, word_list, strategy, rng)
+        .ok_or(HangmanError::NoWordsLeft)?;
+    // whatever the strategy picked, it has to be a real bucket whose words all give that signature
+    debug_assert!(buckets.get(&chosen).is_some_and(|bucket| bucket.iter().all(|&i| word_signature(&word_list[i], guess) == chosen)),
+        "strategy picked signature {:b} for {}, which doesn't fit its words", chosen, guess);
+    let survivors = buckets[&chosen].clone();
+    Ok(GuessOutcome { buckets, chosen, revealed_count: chosen.matches_count(), survivors })
+}
+
+// What do_guess_by_size decided. The same as a GuessOutcome, but only the chosen bucket
+// has its words; the rest are just sizes.
+#[derive(Debug, Clone)]
+pub struct SizedOutcome {
+    pub sizes: BucketSizes,
+    pub chosen: GuessSignature,
+    pub revealed_count: u32,
+    pub survivors: Vec<usize>,
+}
+
+// do_guess for strategies that only look at bucket sizes: count how many of the words at the
+// indices in among land in each bucket, let the strategy pick, and then keep the ones with
+// the chosen signature. The signatures are only worked out once, and with the same rng it
+// comes out exactly like do_guess on just those words.
+pub fn do_guess_by_size(guess: char, word_list: &[String], among: &[usize], strategy: &dyn ChoosesBySize, rng: &mut dyn RngCore) -> Result<SizedOutcome> {
+    let signatures = word_signatures(word_list, among, guess);
+    let mut sizes: BucketSizes = HashMap::new();
+    for &sig in &signatures {
+        *sizes.entry(sig).or_insert(0) += 1;
     }
+    if sizes.is_empty() { return Err(HangmanError::NoWordsLeft); }
+    let chosen = strategy.choose_by_size(&sizes, rng);
+    let survivors = among.iter().zip(&signatures).filter(|(_, sig)| **sig == chosen).map(|(&i, _)| i).collect();
+    Ok(SizedOutcome { sizes, chosen, revealed_count: chosen.matches_count(), survivors })
+}
 
-    // weighted choice sorts the options so we don't need to worry about inconsistent HashMap key orders.
-    weighted_choice(&mut options, rng)
+// 100 points per letter, minus 10 for every guess and a further 40 for every miss,
+// so a miss costs 50 overall. Long words solved quickly score best. Never negative.
+// e.g. a 5 letter word in 8 guesses with 2 misses is 500 - 80 - 80 = 340.
+pub fn score(letter_count: usize, wrong_guesses: u32, total_guesses: usize) -> i64 {
+    let points = 100 * letter_count as i64 - 10 * total_guesses as i64 - 40 * wrong_guesses as i64;
+    points.max(0)
 }
 
-fn do_guess<R: Rng>(guess: char, word_list: &Vec<String>, settings: &Settings, rng: &mut R) -> (HashMap<GuessSignature, Vec<String>>, GuessSignature) {
-    let buckets = guess_buckets(word_list, guess);
-    let guess_result = choose_guess_outcome(&buckets, settings, rng);
-    (buckets, guess_result)
+
+// Where the words a game of this length can use are in the dictionary, in order.
+// Errors rather than giving back none, since nothing downstream can cope with an empty list.
+pub fn words_of_length(word_list: &[String], letter_count: usize) -> Result<Vec<usize>> {
+    if letter_count > MAX_LETTERS {
+        return Err(HangmanError::TooManyLetters { letter_count, max: MAX_LETTERS });
+    }
+    let indices: Vec<usize> = (0..word_list.len()).filter(|&i| letter_len(&word_list[i]) == letter_count).collect();
+    if indices.is_empty() {
+        return Err(HangmanError::EmptyWordList { letter_count });
+    }
+    Ok(indices)
 }
 
-pub fn initialize_game(settings: &Settings) -> History {
-    let mut buffer = String::new();
-    let mut rng_seed: u64 = 0;
-    let mut letter_count: usize = 0;
-    println!("No history file found, initializing a new game.");
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::strategy::ClassicEvil;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+    use std::cell::Cell;
+
+    thread_local! {
+        // how many times word_signature has run on this thread
+        static SIGNATURES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    pub(super) fn signature_taken() {
+        SIGNATURES.set(SIGNATURES.get() + 1);
+    }
+
+    pub(crate) fn signatures() -> usize {
+        SIGNATURES.get()
+    }
 
-    loop {
-        buffer.clear();
-        print!("Random seed? ");
-        io::stdout().flush();
-        let result = || -> Result<u64> {
-            io::stdin().read_line(&mut buffer)?;
-            println!("{}", &buffer);
-            Ok(buffer.trim().parse()?)
-        }();
-        match result {
-            Ok(s) => {rng_seed = s; break }
-            Err(_) => println!("I couldn't read that, try again."),
+    // The count is per thread, so anything counted has to be run in here for rayon's
+    // threads not to take some of the signatures off where they can't be seen.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn on_one_thread<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+        rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap().install(f)
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) fn on_one_thread<T>(f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    // what the debug check in do_guess_bucketed costs on top, when there is one
+    fn checked(survivors: usize) -> usize {
+        if cfg!(debug_assertions) { survivors } else { 0 }
+    }
+
+    // Past u64's 64 bits, so the signature has to be wider than that to keep up.
+    #[test]
+    fn signatures_fit_words_past_64_letters() {
+        let word: String = (0..70).map(|i| if i % 7 == 3 || i == 69 { 'E' } else { 'X' }).collect();
+        let sig = word_signature(&word, 'E');
+        assert_eq!(sig.matches_count(), 11);
+        let bits: Vec<bool> = sig.decode(70).collect();
+        assert_eq!(bits.len(), 70);
+        assert!(bits.iter().enumerate().all(|(i, &b)| b == (i % 7 == 3 || i == 69)));
+        assert_eq!(display_signature(sig, &"_".repeat(70), 'E'), word.replace('X', "_"));
+        assert_eq!(GuessSignature::full(70).matches_count(), 70);
+        assert_eq!(GuessSignature::full(MAX_LETTERS).matches_count() as usize, MAX_LETTERS);
+    }
+
+    #[test]
+    fn words_longer_than_a_signature_are_refused() {
+        let list = vec!["E".repeat(MAX_LETTERS + 1)];
+        assert!(matches!(words_of_length(&list, MAX_LETTERS + 1), Err(HangmanError::TooManyLetters { .. })));
+    }
+
+    // CAFÉ is five bytes but four letters, and has to be counted as four everywhere.
+    #[test]
+    fn accented_words_count_letters_not_bytes() {
+        assert_eq!(letter_len("CAFÉ"), 4);
+        let list = words(&["CAFÉ", "NAÏVE", "CAFES", "SOUP"]);
+        assert_eq!(words_of_length(&list, 4).unwrap(), [0, 3]);
+        assert_eq!(words_of_length(&list, 5).unwrap(), [1, 2]);
+        assert_eq!(length_histogram(&list), BTreeMap::from([(4, 2), (5, 2)]));
+
+        let sig = word_signature("CAFÉ", 'É');
+        assert_eq!(sig, GuessSignature::from_bits(0b1000));
+        assert_eq!(display_signature(sig, "C___", 'É'), "C__É");
+        assert_eq!(display_signature(word_signature("NAÏVE", 'E'), "_AÏ__", 'E'), "_AÏ_E");
+    }
+
+    #[test]
+    fn scores_reward_long_words_and_few_misses() {
+        // the example from score's comment
+        assert_eq!(score(5, 2, 8), 340);
+        assert_eq!(score(5, 0, 5), 450);
+        assert_eq!(score(8, 0, 5), 750);
+        // a miss costs 50 in all: the guess and 40 more
+        assert_eq!(score(5, 3, 9) - score(5, 2, 8), -50);
+        assert_eq!(score(3, 10, 20), 0);
+    }
+
+    // Word guesses are saved and read back as themselves, not as their first letter.
+    #[test]
+    fn guesses_round_trip_through_strings() {
+        for guess in [Guess::Letter('E'), Guess::Word("CRANE".to_string()), Guess::Hint, Guess::GiveUp] {
+            assert_eq!(Guess::try_from(String::from(guess.clone())).unwrap(), guess);
         }
+        assert_eq!(Guess::try_from("crane".to_string()).unwrap(), Guess::Word("CRANE".to_string()));
+        assert!(Guess::try_from("C4".to_string()).is_err());
+        assert!(Guess::try_from(String::new()).is_err());
     }
-    
-    loop {
-        buffer.clear();
-        print!("Number of letters? ");
-        io::stdout().flush();
-        let result =  || -> Result<usize> {
-            io::stdin().read_line(&mut buffer)?;
-            println!("{}", &buffer);
-            Ok(buffer.trim().parse()?)
-        }();
-        match result {
-            Ok(l) => {
-                if l < 1 || l > 64 {
-                    println!("Number of letters must be between 1 and 64 inclusive.");
-                } else {
-                    letter_count = l; 
-                    break 
-                }
+
+    // Each preset is meaner than the one before, on shorter words.
+    #[test]
+    fn difficulties_get_harder_in_every_way() {
+        let [easy, medium, hard] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard].map(Difficulty::parameters);
+        assert!(easy.0 < medium.0 && medium.0 < hard.0);
+        assert!(easy.1 < medium.1 && medium.1 < hard.1);
+        assert!(easy.2.start() > medium.2.start() && medium.2.start() > hard.2.start());
+        assert!(easy.2.end() > medium.2.end() && medium.2.end() > hard.2.end());
+        // steep enough that the biggest bucket is nearly always kept
+        assert!(hard.0 >= 4.0);
+        assert!(easy.2.end() <= &MAX_LETTERS);
+    }
+
+    #[test]
+    fn a_difficulty_overrides_the_evil_settings() {
+        let mut settings = Settings { evil_exponent: 7.0, evil_factor: 3.0, difficulty: Some(Difficulty::Medium), ..Settings::default() };
+        settings.apply_difficulty();
+        assert_eq!((settings.evil_exponent, settings.evil_factor), (1.0, 1.25));
+        let mut manual = Settings { evil_exponent: 7.0, evil_factor: 3.0, ..Settings::default() };
+        manual.apply_difficulty();
+        assert_eq!((manual.evil_exponent, manual.evil_factor), (7.0, 3.0));
+    }
+
+    // Enough made-up words that rayon splits them over threads, when it's in.
+    fn many_words() -> Vec<String> {
+        let mut rng = ChaCha12Rng::seed_from_u64(21);
+        (0..20_000).map(|_| (0..6).map(|_| rng.random_range('A'..='H')).collect()).collect()
+    }
+
+    fn sorted(mut buckets: Buckets) -> BTreeMap<GuessSignature, Vec<usize>> {
+        buckets.values_mut().for_each(|bucket| bucket.sort());
+        buckets.into_iter().collect()
+    }
+
+    // Whichever way they're worked out, the buckets hold the same words.
+    #[test]
+    fn buckets_match_the_sequential_ones() {
+        let list = many_words();
+        let among: Vec<usize> = (0..list.len()).filter(|i| i % 3 != 0).collect();
+        for guess in ['A', 'E', 'Z'] {
+            let expected = guess_buckets_sequential(&list, guess);
+            assert_eq!(sorted(guess_buckets(&list, guess)), sorted(expected.clone()));
+            let sizes: BucketSizes = expected.iter().map(|(sig, bucket)| (*sig, bucket.len())).collect();
+            assert_eq!(guess_bucket_sizes(&list, guess), sizes);
+
+            let among_expected: Buckets = expected.into_iter()
+                .map(|(sig, bucket)| (sig, bucket.into_iter().filter(|i| i % 3 != 0).collect::<Vec<_>>()))
+                .filter(|(_, bucket)| !bucket.is_empty())
+                .collect();
+            assert_eq!(sorted(guess_buckets_among(&list, &among, guess)), sorted(among_expected));
+            let signatures: Vec<GuessSignature> = among.iter().map(|&i| word_signature(&list[i], guess)).collect();
+            assert_eq!(word_signatures(&list, &among, guess), signatures);
+        }
+    }
+
+    // However bad the weights, nothing panics; anything with a positive weight gets picked
+    // only from among those, and nothing does if none has one.
+    #[test]
+    fn weighted_choice_survives_bad_weights() {
+        let bad = [f64::NAN, -1.0, f64::INFINITY, f64::NEG_INFINITY, 0.0, -0.0, f64::MAX, f64::MIN_POSITIVE];
+        let mut rng = ChaCha12Rng::seed_from_u64(24);
+        for _ in 0..500 {
+            let len = rng.random_range(0..6);
+            let weights: Vec<f64> = (0..len).map(|_| if rng.random_bool(0.3) { rng.random_range(0.1..10.0) } else { bad[rng.random_range(0..bad.len())] }).collect();
+            let usable = |w: f64| w.is_finite() && w > 0.0;
+            let picked = weighted_choice_index(&weights, &mut rng);
+            match picked {
+                Some(i) => assert!(usable(weights[i]), "picked {} from {:?}", weights[i], weights),
+                None => assert!(!weights.iter().any(|&w| usable(w)), "picked nothing from {:?}", weights),
             }
-            Err(_) => println!("I couldn't read that, try again."),
+            let mut options: Vec<(f64, usize)> = weights.iter().copied().zip(0..).collect();
+            assert_eq!(weighted_choice(&mut options, &mut rng).is_some(), picked.is_some());
         }
+        // two weights so big their sum overflows still pick one of them
+        assert!(weighted_choice_index(&[f64::MAX, f64::MAX], &mut rng).is_some());
+        assert_eq!(weighted_choice_index(&[0.0, 0.0], &mut rng), None);
+        assert_eq!(weighted_choice_index(&[], &mut rng), None);
     }
 
-    History { rng_seed, letter_count: letter_count, guesses: Vec::new()}
-}
+    // Pinned, since a game replays only if the same seed always draws the same index.
+    #[test]
+    fn weighted_choice_index_is_pinned_for_a_seed() {
+        let weights = [1.0, 5.0, 0.5, 3.0, 0.0];
+        let picks: Vec<usize> = (0..8).map(|seed| weighted_choice_index(&weights, &mut ChaCha12Rng::seed_from_u64(seed)).unwrap()).collect();
+        assert_eq!(picks, [1, 1, 3, 0, 1, 3, 3, 2]);
+        // weighted_choice draws the same way, once its options are in order
+        let mut options: Vec<(f64, char)> = weights.iter().copied().zip('a'..).collect();
+        let mut sorted = options.clone();
+        sorted.sort_by(|(w1, _), (w2, _)| w1.total_cmp(w2));
+        let sorted_weights: Vec<f64> = sorted.iter().map(|(w, _)| *w).collect();
+        for seed in 0..8 {
+            let index = weighted_choice_index(&sorted_weights, &mut ChaCha12Rng::seed_from_u64(seed)).unwrap();
+            assert_eq!(weighted_choice(&mut options, &mut ChaCha12Rng::seed_from_u64(seed)), Some(sorted[index].1));
+        }
+    }
 
-fn save_history(history: &History, settings: &Settings) {
-    print!("Saving history... ");
-    io::stdout().flush();
-    history.write_to_file(&settings.history_path);
-    println!("Done!");
-}
+    #[test]
+    fn no_words_of_a_length_is_an_error() {
+        let list = words(&["CAT", "DOG"]);
+        assert!(matches!(words_of_length(&list, 5), Err(HangmanError::EmptyWordList { letter_count: 5 })));
+        assert_eq!(words_of_length(&list, 3).unwrap(), [0, 1]);
+    }
 
-fn replay_history<R: Rng>(word_list: &mut Vec<String>, history: &History, settings: &Settings, rng: &mut R) -> String {
-    word_list.retain(|word| word.len() == history.letter_count);
-    if settings.verbose {println!("{} words of length {}", word_list.len(), history.letter_count);}
-    let mut word_info = iter::repeat_n('_', history.letter_count).collect();
+    // Nothing to pick from comes back as an error, not a panic.
+    #[test]
+    fn guessing_with_no_words_is_an_error() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0);
+        assert_eq!(choose_guess_outcome(&Buckets::new(), &[], &ClassicEvil, &mut rng), None);
+        assert!(matches!(do_guess('E', &[], &ClassicEvil, &mut rng), Err(HangmanError::NoWordsLeft)));
+        assert!(matches!(do_guess_by_size('E', &words(&["CAT"]), &[], &ClassicEvil, &mut rng), Err(HangmanError::NoWordsLeft)));
+    }
 
-    for (n, &guess) in history.guesses.iter().enumerate() {
-        print!("Guess #{}: {}  ", n, guess);
-        let (mut buckets, guess_result) = do_guess(guess, word_list, settings, rng);
-        word_info = display_signature(guess_result, &word_info, guess);
-        *word_list = buckets.remove(&guess_result).unwrap();
-        if(settings.verbose) { println!("Result: {}  Remaining Words: {}", &word_info, word_list.len()); }
+    #[test]
+    fn sized_guesses_sign_each_word_once() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "YAK", "CRANE"]);
+        let among = [0, 1, 2, 3, 5, 6];
+        let (sized, counted) = on_one_thread(|| {
+            let start = signatures();
+            let outcome = do_guess_by_size('O', &list, &among, &ClassicEvil, &mut ChaCha12Rng::seed_from_u64(0)).unwrap();
+            assert_eq!(outcome.sizes.values().sum::<usize>(), among.len());
+            let sized = signatures() - start;
+            guess_bucket_sizes(&list, 'O');
+            (sized, signatures() - start - sized)
+        });
+        assert_eq!(sized, among.len());
+        assert_eq!(counted, list.len());
     }
 
-    word_info
-}
+    // The buckets feed the strategy and the survivors without the words being gone over again.
+    #[test]
+    fn bucketed_guesses_sign_each_word_once() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "YAK"]);
+        let (bucketing, choosing, whole, survivors) = on_one_thread(|| {
+            let mut rng = ChaCha12Rng::seed_from_u64(0);
+            let start = signatures();
+            let buckets = guess_buckets(&list, 'O');
+            let bucketing = signatures() - start;
+            let outcome = do_guess_bucketed('O', buckets, &list, &ClassicEvil, &mut rng).unwrap();
+            let choosing = signatures() - start - bucketing;
+            let start = signatures();
+            do_guess('O', &list, &ClassicEvil, &mut rng).unwrap();
+            (bucketing, choosing, signatures() - start, outcome.survivors.len())
+        });
+        assert_eq!(bucketing, list.len());
+        assert_eq!(choosing, checked(survivors));
+        assert_eq!(whole, list.len() + checked(survivors));
+    }
 
-fn read_guess() -> char {
-    let mut buffer = String::new();
-    let mut guess = '_';
-    loop {
-        buffer.clear();
-        print!("Next guess? ");
-        io::stdout().flush();
-        let result = || -> Result<char> {
-            io::stdin().read_line(&mut buffer)?;
-            let c = buffer.chars().next().ok_or(Error::msg("Need at least one char"))?;
-            if c.is_ascii_alphabetic() {
-                Ok(c.to_ascii_uppercase())
-            } else {
-                Err(Error::msg("Need an ascii alphabetic character."))
-            }
-        }();
-        match result {
-            Ok(c) => {guess = c; break }
-            Err(_) => println!("I couldn't read that, try again."),
+    #[test]
+    fn slots_go_before_the_extension() {
+        let settings = Settings { history_path: "histories/h.toml".to_string(), ..Settings::default() };
+        assert_eq!(settings.history_file(), "histories/h.toml");
+        assert_eq!(Settings { slot: Some("3".to_string()), ..settings.clone() }.history_file(), "histories/h.3.toml");
+        let bare = Settings { history_path: "history".to_string(), slot: Some("work".to_string()), ..Settings::default() };
+        assert_eq!(bare.history_file(), "history.work");
+        assert!(Settings { slot: Some("../up".to_string()), ..settings }.validate().is_err());
+    }
+
+    // What validate had to say, which has to name the field at fault.
+    fn invalid(settings: Settings) -> String {
+        match settings.validate() {
+            Err(HangmanError::InvalidSettings(message)) => message,
+            other => panic!("expected invalid settings, got {:?}", other),
         }
     }
-    guess
-}
 
-pub fn play_game(mut word_list: Vec<String>, opt_history: Option<History>, settings: &Settings, rng_salt: u64) {
-    let mut history = opt_history.unwrap_or_else(|| initialize_game(settings));
-    let mut rng = ChaCha12Rng::seed_from_u64(rng_salt ^ history.rng_seed);
-    let mut word_info = replay_history(&mut word_list, &history, settings, &mut rng);
-    save_history(&history, settings);
-    loop {
-        let guess = read_guess();
-        let (mut buckets, guess_result) = do_guess(guess, &word_list, settings, &mut rng);
-        history.guesses.push(guess);
+    #[test]
+    fn default_settings_are_valid() {
+        assert!(Settings::default().validate().is_ok());
+        assert!(Settings { evil_exponent: -2.0, evil_factor: 0.01, ..Settings::default() }.validate().is_ok());
+    }
+
+    #[test]
+    fn bad_evil_numbers_are_refused_by_name() {
+        for factor in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(invalid(Settings { evil_factor: factor, ..Settings::default() }).starts_with("evil_factor"), "{}", factor);
+        }
+        for exponent in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(invalid(Settings { evil_exponent: exponent, ..Settings::default() }).starts_with("evil_exponent"), "{}", exponent);
+        }
+    }
+
+    #[test]
+    fn empty_paths_are_refused_by_name() {
+        let empty = String::from("  ");
+        assert_eq!(invalid(Settings { word_list_path: WordListPath::One(empty.clone()), ..Settings::default() }), "word_list_path can't be empty");
+        assert_eq!(invalid(Settings { word_list_path: WordListPath::Many(Vec::new()), ..Settings::default() }), "word_list_path needs at least one file in it");
+        assert_eq!(invalid(Settings { exclusions_list_path: empty.clone(), ..Settings::default() }), "exclusions_list_path can't be empty");
+        assert_eq!(invalid(Settings { salt_file_path: empty.clone(), ..Settings::default() }), "salt_file_path can't be empty");
+        assert_eq!(invalid(Settings { history_path: empty, ..Settings::default() }), "history_path can't be empty");
+    }
+
+    // TOOT counts once for T and once for O, however many of them it has.
+    #[test]
+    fn letter_frequencies_count_words_and_skip_guessed_letters() {
+        let words = ["CAT", "COT", "DOG", "TOOT"];
+        assert_eq!(letter_frequencies(&words, &[]), [('O', 3), ('T', 3), ('C', 2), ('A', 1), ('D', 1), ('G', 1)]);
+        assert_eq!(letter_frequencies(&words, &['O', 'C']), [('T', 3), ('A', 1), ('D', 1), ('G', 1)]);
+        assert!(letter_frequencies(&words, &['A', 'C', 'D', 'G', 'O', 'T']).is_empty());
+    }
+
+    #[test]
+    fn the_best_guess_is_in_the_most_words() {
+        let words = ["BEE", "SEA", "TEN", "CAT"];
+        assert_eq!(best_guess(&words, &[]), Some('E'));
+        assert_eq!(best_guess(&words, &['E']), Some('A'));
+        assert_eq!(best_guess(&words, &['A', 'B', 'C', 'E', 'N', 'S', 'T']), None);
+    }
+
+    #[test]
+    fn the_miss_bucket_is_the_words_without_the_letter() {
+        let words = ["CAT", "COT", "DOG", "OWL"];
+        assert_eq!(zero_match_bucket(&words, 'T'), Some(2));
+        assert_eq!(zero_match_bucket(&words, 'Z'), Some(4));
+        assert_eq!(zero_match_bucket(&["COT", "DOG", "OWL"], 'O'), None);
+    }
 
-        if settings.verbose { display_guess_statistics(&buckets, &word_info, guess); }
-        word_info = display_signature(guess_result, &word_info, guess);
-        word_list = buckets.remove(&guess_result).unwrap();
-        if(settings.verbose) { 
-            println!("Result: {}  Remaining Words: {}", &word_info, word_list.len());
-            println!("Guesses so far: {}", history.guesses.iter().collect::<String>())
+    // CAFÉ is four letters, however many bytes the É takes.
+    #[test]
+    fn lengths_are_counted_in_letters_shortest_first() {
+        let words: Vec<String> = ["OX", "CAT", "DOG", "CAFÉ", "LION", "BEAR", "ZEBRAS"].iter().map(|w| w.to_string()).collect();
+        assert_eq!(length_histogram(&words).into_iter().collect::<Vec<_>>(), [(2, 1), (3, 2), (4, 3), (6, 1)]);
+        assert!(length_histogram(&[]).is_empty());
+    }
+
+    #[test]
+    fn survivors_are_the_chosen_bucket() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "YAK", "TOT"]);
+        let strategy = Settings::default().strategy.build(&Settings::default(), &list, None, &mut ChaCha12Rng::seed_from_u64(0));
+        for seed in 0..8 {
+            let outcome = do_guess('O', &list, strategy.as_ref(), &mut ChaCha12Rng::seed_from_u64(seed)).unwrap();
+            assert_eq!(outcome.survivors, outcome.buckets[&outcome.chosen]);
+            assert_eq!(outcome.revealed_count, outcome.chosen.matches_count());
+            assert!(outcome.survivors.iter().all(|&i| word_signature(&list[i], 'O') == outcome.chosen));
+        }
+        let sized = do_guess_by_size('T', &list, &[0, 1, 8], &ClassicEvil, &mut ChaCha12Rng::seed_from_u64(0)).unwrap();
+        assert_eq!(sized.sizes[&sized.chosen], sized.survivors.len());
+        assert_eq!(sized.revealed_count, sized.chosen.matches_count());
+    }
+
+    // The sized path on some of the words comes out just like do_guess on only those words.
+    #[test]
+    fn sized_guesses_pick_like_full_ones() {
+        let list = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "YAK", "TOT", "COO"]);
+        let among = [0, 1, 2, 4, 5, 8, 9];
+        let subset: Vec<String> = among.iter().map(|&i| list[i].clone()).collect();
+        let settings = Settings::default();
+        let weighted = settings.strategy.build(&settings, &list, None, &mut ChaCha12Rng::seed_from_u64(0));
+        let strategies: [(&dyn GuessStrategy, &dyn ChoosesBySize); 2] = [(&ClassicEvil, &ClassicEvil), (weighted.as_ref(), weighted.by_size().unwrap())];
+        for (full, by_size) in strategies {
+            for guess in ['O', 'T', 'C', 'Z'] {
+                for seed in 0..10 {
+                    let outcome = do_guess(guess, &subset, full, &mut ChaCha12Rng::seed_from_u64(seed)).unwrap();
+                    let sized = do_guess_by_size(guess, &list, &among, by_size, &mut ChaCha12Rng::seed_from_u64(seed)).unwrap();
+                    assert_eq!(sized.chosen, outcome.chosen, "{} with seed {}", guess, seed);
+                    assert_eq!(sized.revealed_count, outcome.revealed_count);
+                    let survivors: Vec<usize> = outcome.survivors.iter().map(|&i| among[i]).collect();
+                    assert_eq!(sized.survivors, survivors);
+                    assert_eq!(sized.sizes, guess_bucket_sizes(&subset, guess));
+                }
+            }
         }
-        save_history(&history, settings); 
-        if !word_info.contains('_') { break }
     }
-    println!("Winner! The word was {}", &word_info);
+
+    #[test]
+    fn signature_methods() {
+        let sig = word_signature("BANANA", 'A');
+        assert_eq!(sig, GuessSignature::from_bits(0b101010));
+        assert_eq!(sig.bits(), 0b101010);
+        assert_eq!(sig.matches_count(), 3);
+        assert_eq!(sig.decode(6).collect::<Vec<_>>(), [false, true, false, true, false, true]);
+        assert!(GuessSignature::empty().is_empty() && !sig.is_empty());
+        assert_eq!(GuessSignature::empty(), word_signature("BANANA", 'E'));
+        assert_eq!(GuessSignature::default(), GuessSignature::empty());
+        assert_eq!(GuessSignature::full(4), GuessSignature::from_bits(0b1111));
+        assert_eq!(GuessSignature::full(0), GuessSignature::empty());
+    }
+
+    // Buckets get sorted by signature, so this has to be the order of the bits as a number.
+    #[test]
+    fn signatures_sort_like_their_bits() {
+        let mut sigs: Vec<GuessSignature> = [0b100, 0b1, 0b11, 0].map(GuessSignature::from_bits).to_vec();
+        sigs.sort();
+        assert_eq!(sigs.iter().map(|s| s.bits()).collect::<Vec<_>>(), [0, 0b1, 0b11, 0b100]);
+        assert_eq!(format!("{:b}", GuessSignature::from_bits(0b101)), "101");
+    }
+
+    #[test]
+    fn signature_bits_go_either_way_and_know_their_length() {
+        let sig = GuessSignature::from_bits(0b10011);
+        let bits = sig.decode(6);
+        assert_eq!(bits.len(), 6);
+        assert_eq!(bits.clone().collect::<Vec<_>>(), [true, true, false, false, true, false]);
+        assert_eq!(bits.rev().collect::<Vec<_>>(), [false, true, false, false, true, true]);
+
+        // from both ends at once, meeting in the middle
+        let mut bits = sig.decode(6);
+        assert_eq!((bits.next(), bits.next_back(), bits.len()), (Some(true), Some(false), 4));
+        assert_eq!(bits.next_back(), Some(true));
+        assert_eq!(bits.by_ref().collect::<Vec<_>>(), [true, false, false]);
+        assert_eq!((bits.next(), bits.next_back(), bits.len()), (None, None, 0));
+        assert_eq!(sig.decode(0).len(), 0);
+    }
+
+    // O halves them exactly, D only picks out DOG, and Z leaves them all together.
+    #[test]
+    fn even_splits_carry_the_most_information() {
+        let list = words(&["CAT", "COT", "DOG", "PIG"]);
+        assert_eq!(guess_entropy(&list, 'O'), 1.0);
+        assert!((guess_entropy(&list, 'D') - 0.8112781).abs() < 1e-6, "{}", guess_entropy(&list, 'D'));
+        assert_eq!(guess_entropy(&list, 'Z'), 0.0);
+        // every word in a bucket of its own is the most there is
+        assert_eq!(guess_entropy(&words(&["CAT", "ACT", "TAC", "DOG"]), 'C'), 2.0);
+    }
 }
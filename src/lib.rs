@@ -0,0 +1,20 @@
+// The hangman engine. Everything here is free of terminal I/O so it can be
+// embedded elsewhere; main.rs is just a command line frontend on top of it.
+
+pub mod auto;
+pub mod error;
+pub mod hangman;
+pub mod game;
+// files on disk: settings, histories, word lists and the like
+#[cfg(feature = "word-list-loading")]
+pub mod loading;
+pub mod proof;
+pub mod seed;
+pub mod share;
+pub mod strategy;
+pub mod transcript;
+
+pub use error::HangmanError;
+pub use game::{Game, GameOutcome, GuessResult, HintResult};
+pub use hangman::{GameStatus, Guess, Settings, SettingsBuilder, History};
+pub use strategy::{GuessStrategy, Strategy};
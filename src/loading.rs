@@ -0,0 +1,898 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::{HangmanError, Result};
+use crate::hangman::{self, Settings, History, WordWeights};
+use crate::proof::Proof;
+use crate::transcript::{self, TurnRecord};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SeedSalt {
+    salt: u64,
+}
+
+impl SeedSalt {
+    pub fn from_file(path: &str) -> Result<u64> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| HangmanError::io(format!("Failed to read seed salt file {}", path), e))?;
+        let sd: SeedSalt = toml::from_str(&content)
+            .map_err(|e| HangmanError::parse(format!("Failed to parse seed salt file {}", path), e))?;
+
+        Ok(sd.salt)
+    }
+}
+
+impl Settings {
+    /// Load settings from a TOML file
+    pub fn from_file(path: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| HangmanError::io(format!("Failed to read settings file {}", path), e))?;
+        toml::from_str(&content)
+            .map_err(|e| HangmanError::parse(format!("Failed to parse settings file {}", path), e))
+    }
+
+    // Leaves any existing file alone, since it's most likely been edited by hand.
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        if Path::new(path).exists() {
+            return Err(HangmanError::io(format!("Not overwriting settings file {}", path), io::ErrorKind::AlreadyExists.into()));
+        }
+        let settings_string = toml::to_string_pretty(self)
+            .map_err(|e| HangmanError::parse("Failed to serialize settings", e))?;
+        write_atomically(path, &(SETTINGS_HEADER.to_string() + &settings_string))
+            .map_err(|e| HangmanError::io(format!("Failed to write settings file {}", path), e))
+    }
+}
+
+const SETTINGS_HEADER: &str = "\
+# Settings for manifold_hangman.
+#
+# evil_exponent and evil_factor set how hard the adversary leans towards keeping the most
+# words alive: higher is meaner. A difficulty of \"easy\", \"medium\" or \"hard\" overrides both.
+# evil_exponent_start and evil_exponent_end, if given, ramp the exponent as letters get revealed.
+# word_list_path is one word per line, optionally followed by a tab and its frequency.
+# It can also be a list of such files, e.g. [\"animals.txt\", \"plants.txt\"], to merge them.
+# exclusions_list_path lists words never to use, and can be empty.
+# salt_file_path holds `salt = <number>`, kept secret so seeds can't be worked backwards.
+# history_path is where the game in progress is saved; a .json extension saves it as JSON.
+# The optional strategy picks how the adversary plays: \"weighted\" (the default),
+# \"classic\", \"fair\" or \"minimax\", which looks minimax_depth guesses ahead (1 or 2).
+# verbosity goes from 0 (quiet) to 3 (the adversary's workings), 1 by default; an old
+# verbose = true counts as 2. RUST_LOG, e.g. RUST_LOG=debug, overrides it for 2 and 3.
+# The [prompts] table rewords what the game says, e.g. next_guess = \"Prochaine lettre ? \";
+# won, gave_up and lost put the word where their {} is.
+# category plays only words under a \"# category: NAME\" line in the word lists, and shows
+# it as a clue; \"random\" picks a different one for each game.
+# definitions_path has a word, a tab and its definition on each line; you get told the
+# definition when you win with that word.
+#
+# Other options, all optional: max_wrong_guesses, daily, use_frequencies,
+# save_snapshots, color, slot, transcript_path, blank_char, output, stats_limit,
+# proof_path, restrict_impossible, reveal_mode, hide_seed, word_filter_regex,
+# normalize_accents, allowed_letters, blocklist_path, reveal_hints, evil_position_factor.
+
+";
+
+impl History {
+    // A missing file just means there's no game in progress, so that's Ok(None).
+    // A file that's there but won't parse is an actual error.
+    pub fn from_file(path: &str) -> Result<Option<Self>> {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(HangmanError::io(format!("Failed to read history file {}", path), e)),
+        };
+        let history = HistoryFormat::of(path).parse(&content)
+            .map_err(|e| HangmanError::parse(format!("Failed to parse history file {}", path), e))?
+            .migrate()?;
+        Ok(Some(history))
+    }
+
+    pub fn write_to_file(&self, path: &str) -> Result<()> {
+        let history_string = HistoryFormat::of(path).serialize(self)
+            .map_err(|e| HangmanError::parse("Failed to serialize history", e))?;
+        write_atomically(path, &history_string)
+            .map_err(|e| HangmanError::io(format!("Failed to write history file {}", path), e))
+    }
+}
+
+// The word lists the settings ask for, with their exclusions, blocklist, word_filter_regex
+// and allowed_letters applied. With a letter_count, words of any other length are skipped
+// as they're read, which saves a lot of memory on a big dictionary when that's all a game needs.
+pub fn read_settings_word_list(settings: &Settings, letter_count: Option<usize>) -> Result<Vec<(String, f64)>> {
+    Ok(read_settings_categorized(settings, letter_count)?.0)
+}
+
+// Which words each category in the word lists has. A line like "# category: Animals" in a
+// word list puts the words after it, up to the next such line or the end of the file, in
+// that category; names are uppercased. Words before any of them aren't in one.
+pub type Categories = BTreeMap<String, HashSet<String>>;
+
+// read_settings_word_list along with the categories. settings.category, unless it's
+// "random", keeps just the words of that category.
+pub fn read_settings_categorized(settings: &Settings, letter_count: Option<usize>) -> Result<(Vec<(String, f64)>, Categories)> {
+    let filter = match &settings.word_filter_regex {
+        Some(pattern) => Some(Regex::new(pattern)
+            .map_err(|e| HangmanError::InvalidSettings(format!("word_filter_regex {:?} isn't a valid regex: {}", pattern, e)))?),
+        None => None,
+    };
+    let (mut words, categories) = read_lists(settings.word_list_path.paths(), &settings.exclusions_list_path, settings.normalize_accents, letter_count)?;
+    // kept apart from the exclusions, which are for tuning a game rather than never wanting a word
+    if let Some(path) = &settings.blocklist_path {
+        let blocked = read_word_set(path, "blocklist", settings.normalize_accents)?;
+        words.retain(|(word, _)| !blocked.contains(word));
+    }
+    if let Some(filter) = filter {
+        words.retain(|(word, _)| filter.is_match(word));
+    }
+    words.retain(|(word, _)| word.chars().all(|c| settings.allows(c)));
+    if let Some(name) = settings.fixed_category() {
+        let Some(category) = categories.get(&name) else {
+            return Err(HangmanError::InvalidSettings(format!("No category {} in the word lists", name)));
+        };
+        words.retain(|(word, _)| category.contains(word));
+    }
+    Ok((words, categories))
+}
+
+// Settings and the dictionary they pick out, kept together so the settings can be re-read
+// between games without re-reading a big word list that hasn't changed.
+#[derive(Debug, Clone)]
+pub struct Dictionary {
+    pub settings: Settings,
+    pub word_list: Vec<String>,
+    // only with settings.use_frequencies
+    pub weights: Option<Arc<WordWeights>>,
+    // if it only has words of this length; see read_settings_word_list
+    pub letter_count: Option<usize>,
+    // empty if the word lists don't have any
+    pub categories: Categories,
+    // from settings.definitions_path, if there is one
+    pub definitions: HashMap<String, String>,
+}
+
+// What a reload changed, for telling the player about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reloaded {
+    // the adversary's parameters: the evil ones, difficulty, strategy or minimax_depth
+    pub evil_changed: bool,
+    pub words_reloaded: bool,
+}
+
+impl Dictionary {
+    pub fn load(settings: Settings, letter_count: Option<usize>) -> Result<Self> {
+        let (weighted_words, categories) = read_settings_categorized(&settings, letter_count)?;
+        let weights = settings.use_frequencies.then(|| Arc::new(weighted_words.iter().cloned().collect()));
+        let word_list = weighted_words.into_iter().map(|(word, _)| word).collect();
+        let definitions = match &settings.definitions_path {
+            Some(path) => read_definitions(path, settings.normalize_accents)?,
+            None => HashMap::new(),
+        };
+        Ok(Dictionary { settings, word_list, weights, letter_count, categories, definitions })
+    }
+
+    // Swaps in new settings, which should already be validated. The word lists only get
+    // read again if something that decides which words are in them has changed.
+    pub fn reload(&mut self, settings: Settings) -> Result<Reloaded> {
+        let (old, new) = (&self.settings, &settings);
+        let evil_changed = old.evil_exponent != new.evil_exponent || old.evil_factor != new.evil_factor
+            || old.evil_exponent_start != new.evil_exponent_start || old.evil_exponent_end != new.evil_exponent_end
+            || old.evil_position_factor != new.evil_position_factor || old.difficulty != new.difficulty
+            || old.strategy != new.strategy || old.minimax_depth != new.minimax_depth;
+        let same_words = old.word_list_path == new.word_list_path
+            && old.exclusions_list_path == new.exclusions_list_path
+            && old.blocklist_path == new.blocklist_path
+            && old.word_filter_regex == new.word_filter_regex
+            && old.normalize_accents == new.normalize_accents
+            && old.allowed_letters == new.allowed_letters
+            && old.use_frequencies == new.use_frequencies
+            && old.fixed_category() == new.fixed_category()
+            && old.definitions_path == new.definitions_path;
+        if same_words {
+            self.settings = settings;
+        } else {
+            *self = Dictionary::load(settings, self.letter_count)?;
+        }
+        Ok(Reloaded { evil_changed, words_reloaded: !same_words })
+    }
+}
+
+// letter_count, if there is one, keeps only the words that long.
+pub fn read_word_list(word_path: &str, exclusions_path: &str, letter_count: Option<usize>) -> Result<Vec<String>> {
+    let words = read_weighted_word_list(word_path, exclusions_path, letter_count)?;
+    Ok(words.into_iter().map(|(word, _)| word).collect())
+}
+
+// Lines can be a bare word, or "WORD\tfrequency" to say how common it is.
+// Words without a frequency get 1.0. If a word turns up twice, its first frequency wins.
+pub fn read_weighted_word_list(word_path: &str, exclusions_path: &str, letter_count: Option<usize>) -> Result<Vec<(String, f64)>> {
+    read_weighted_word_lists(&[word_path.to_string()], exclusions_path, letter_count)
+}
+
+// Several lists merged into one, as if they were a single file read in this order,
+// so a word in two of them gets the frequency from the first.
+pub fn read_weighted_word_lists(word_paths: &[String], exclusions_path: &str, letter_count: Option<usize>) -> Result<Vec<(String, f64)>> {
+    Ok(read_lists(word_paths, exclusions_path, false, letter_count)?.0)
+}
+
+// The words of word_list in the category, or all of them for None.
+pub fn category_words(word_list: &[String], categories: &Categories, category: Option<&str>) -> Result<Vec<String>> {
+    let Some(name) = category else { return Ok(word_list.to_vec()) };
+    let words = categories.get(name)
+        .ok_or_else(|| HangmanError::InvalidSettings(format!("No category {} in the word lists", name)))?;
+    Ok(word_list.iter().filter(|word| words.contains(*word)).cloned().collect())
+}
+
+// "WORD\tdefinition" on each line, the words normalized like the word list's so they
+// match it. Lines without a tab are skipped, and the first definition of a word wins.
+pub fn read_definitions(path: &str, fold: bool) -> Result<HashMap<String, String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| HangmanError::io(format!("Failed to read definitions file {}", path), e))?;
+    let mut definitions = HashMap::new();
+    for (word, definition) in content.lines().filter_map(|line| line.split_once('\t')) {
+        let word = if fold { fold_accents(word.trim()) } else { word.trim().to_string() };
+        let definition = definition.trim();
+        if definition.is_empty() { continue; }
+        definitions.entry(word.to_uppercase()).or_insert_with(|| definition.to_string());
+    }
+    Ok(definitions)
+}
+
+// The name out of a "# category: NAME" line, if it's one.
+fn category_header(line: &str) -> Option<String> {
+    let (key, name) = line.trim().strip_prefix('#')?.split_once(':')?;
+    let name = name.trim();
+    (key.trim().eq_ignore_ascii_case("category") && !name.is_empty()).then(|| name.to_uppercase())
+}
+
+// A file of words to leave out, one per line, normalized the way words in the word list
+// are so they compare equal: trimmed, folded if asked, and uppercased.
+fn read_word_set(path: &str, what: &str, fold: bool) -> Result<HashSet<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| HangmanError::io(format!("Failed to read {} file {}", what, path), e))?;
+    Ok(content.lines()
+        .map(|line| if fold { fold_accents(line.trim()) } else { line.trim().to_string() })
+        .filter(|word| hangman::is_all_ascii_alpha(word))
+        .map(|word| word.to_uppercase())
+        .collect())
+}
+
+// With fold, accents come off before anything else looks at a word, exclusions included.
+fn read_lists(word_paths: &[String], exclusions_path: &str, fold: bool, letter_count: Option<usize>) -> Result<(Vec<(String, f64)>, Categories)> {
+    let exclusions = read_word_set(exclusions_path, "exclusions", fold)?;
+    let fold = |word: &str| if fold { fold_accents(word) } else { word.to_string() };
+
+    let mut words = Vec::new();
+    let mut categories = Categories::new();
+    for word_path in word_paths {
+        let read_error = |e| HangmanError::io(format!("Failed to read word list file {}", word_path), e);
+        let words_content = read_maybe_compressed(word_path).map_err(read_error)?;
+        let mut category = None;
+        for (n, line) in words_content.as_str().map_err(read_error)?.lines().enumerate() {
+            if let Some(name) = category_header(line) {
+                category = Some(name);
+                continue;
+            }
+            let (word, frequency) = match line.split_once('\t') {
+                Some((word, freq)) => {
+                    let freq: f64 = freq.trim().parse()
+                        .map_err(|e| HangmanError::parse(format!("Bad frequency on line {} of {}", n + 1, word_path), e))?;
+                    (word, freq)
+                }
+                None => (line, 1.0),
+            };
+            let word = fold(word);
+            if !hangman::is_all_ascii_alpha(&word) {continue;}
+            if letter_count.is_some_and(|n| hangman::letter_len(&word) != n) {continue;}
+            let word = word.to_uppercase();
+            if exclusions.contains(&word) {continue;}   //skip excluded words
+            if let Some(name) = &category {
+                categories.entry(name.clone()).or_default().insert(word.clone());
+            }
+            words.push((word, frequency))
+        }
+    }
+    // stable sort, so dedup keeps the first frequency seen for each word
+    words.sort_by(|a, b| a.0.cmp(&b.0));
+    words.dedup_by(|a, b| a.0 == b.0);
+
+    Ok((words, categories))
+}
+
+// Latin letters with accents and the like, turned into the plain letters they'd be typed
+// as. Anything not in the table is left alone, so the alphabetic check still drops it.
+pub fn fold_accents(word: &str) -> String {
+    let mut folded = String::with_capacity(word.len());
+    for c in word.chars() {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let plain = match lower {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+            'æ' => "ae",
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+            'ď' | 'đ' | 'ð' => "d",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+            'ĥ' | 'ħ' => "h",
+            'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+            'ĵ' => "j",
+            'ķ' => "k",
+            'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+            'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+            'œ' => "oe",
+            'ŕ' | 'ŗ' | 'ř' => "r",
+            'ś' | 'ŝ' | 'ş' | 'š' => "s",
+            'ß' => "ss",
+            'ţ' | 'ť' | 'ŧ' => "t",
+            'þ' => "th",
+            'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+            'ŵ' => "w",
+            'ý' | 'ÿ' | 'ŷ' => "y",
+            'ź' | 'ż' | 'ž' => "z",
+            _ => { folded.push(c); continue; }
+        };
+        folded.push_str(plain);
+    }
+    folded
+}
+
+// Decompresses files ending in .zst, and reads anything else as it is.
+fn read_maybe_compressed(path: &str) -> io::Result<Content> {
+    match Path::new(path).extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("zst") => decompress(path).map(Content::Read),
+        _ => Content::of_file(path),
+    }
+}
+
+// A word list's text. With the mmap feature a plain file gets mapped rather than read in,
+// so a huge list isn't in memory twice over, once as the file and again as the words.
+enum Content {
+    Read(String),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Content {
+    #[cfg(not(feature = "mmap"))]
+    fn of_file(path: &str) -> io::Result<Self> {
+        fs::read_to_string(path).map(Content::Read)
+    }
+
+    #[cfg(feature = "mmap")]
+    fn of_file(path: &str) -> io::Result<Self> {
+        let file = fs::File::open(path)?;
+        // Mapping is only unsafe if the file changes while it's mapped, and it's only kept
+        // for as long as loading takes. Don't edit a word list while it's being loaded.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Content::Mapped(map))
+    }
+
+    // Not UTF-8 is an error, same as from fs::read_to_string.
+    fn as_str(&self) -> io::Result<&str> {
+        match self {
+            Content::Read(s) => Ok(s),
+            #[cfg(feature = "mmap")]
+            Content::Mapped(map) => std::str::from_utf8(map).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decompress(path: &str) -> io::Result<String> {
+    use io::Read;
+    let mut decoder = ruzstd::decoding::StreamingDecoder::new(fs::File::open(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let mut content = String::new();
+    decoder.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_path: &str) -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "it's compressed, and this was built without the compression feature"))
+}
+
+// Writes next to the target and renames over it, so a crash partway through leaves the
+// old file as it was instead of half a new one. The rename only stays atomic within one
+// filesystem, which is why the temporary file goes in the same directory.
+fn write_atomically(path: &str, content: &str) -> io::Result<()> {
+    let temp = format!("{}.tmp", path);
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path)
+}
+
+// Histories are TOML unless the file ends in .json, which is easier for other programs to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HistoryFormat {
+    Toml,
+    Json,
+}
+
+impl HistoryFormat {
+    fn of(path: &str) -> Self {
+        match Path::new(path).extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => HistoryFormat::Json,
+            _ => HistoryFormat::Toml,
+        }
+    }
+
+    fn parse(self, content: &str) -> Result<History, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(match self {
+            HistoryFormat::Toml => toml::from_str(content)?,
+            HistoryFormat::Json => serde_json::from_str(content)?,
+        })
+    }
+
+    fn serialize(self, history: &History) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(match self {
+            HistoryFormat::Toml => toml::to_string_pretty(history)?,
+            HistoryFormat::Json => serde_json::to_string_pretty(history)?,
+        })
+    }
+}
+
+// Markdown if the path ends in .md, plain text otherwise.
+pub fn write_transcript(records: &[TurnRecord], path: &str) -> Result<()> {
+    let content = match Path::new(path).extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("md") => transcript::format_markdown(records),
+        _ => transcript::format_text(records),
+    };
+    fs::write(path, content)
+        .map_err(|e| HangmanError::io(format!("Failed to write transcript file {}", path), e))
+}
+
+// Always JSON, since proofs are for handing to other people and programs.
+pub fn write_proof(proof: &Proof, path: &str) -> Result<()> {
+    let content = serde_json::to_string_pretty(proof)
+        .map_err(|e| HangmanError::parse("Failed to serialize proof", e))?;
+    fs::write(path, content)
+        .map_err(|e| HangmanError::io(format!("Failed to write proof file {}", path), e))
+}
+
+pub fn read_proof(path: &str) -> Result<Proof> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| HangmanError::io(format!("Failed to read proof file {}", path), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| HangmanError::parse(format!("Failed to parse proof file {}", path), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hangman::WordListPath;
+    use crate::strategy::Strategy;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // A directory of its own for each test's files.
+    fn scratch_dir() -> PathBuf {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!("hangman-loading-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Settings reading words from a file with these lines, and nothing excluded.
+    fn settings_for(lines: &[&str]) -> Settings {
+        let dir = scratch_dir();
+        let words = dir.join("words.txt");
+        let exclusions = dir.join("exclusions.txt");
+        fs::write(&words, lines.join("\n")).unwrap();
+        fs::write(&exclusions, "").unwrap();
+        Settings::builder()
+            .word_list_path(words.to_string_lossy())
+            .exclusions_list_path(exclusions.to_string_lossy())
+            .build()
+            .unwrap()
+    }
+
+    fn names(words: &[(String, f64)]) -> Vec<&str> {
+        words.iter().map(|(word, _)| word.as_str()).collect()
+    }
+
+    // Both files in a directory of their own, for read_word_list.
+    fn word_files(words: &[&str], exclusions: &[&str]) -> (String, String) {
+        let dir = scratch_dir();
+        let (words_path, exclusions_path) = (dir.join("words.txt"), dir.join("exclusions.txt"));
+        fs::write(&words_path, words.join("\n")).unwrap();
+        fs::write(&exclusions_path, exclusions.join("\n")).unwrap();
+        (words_path.to_string_lossy().to_string(), exclusions_path.to_string_lossy().to_string())
+    }
+
+    // The default settings as a file, with extra at the top, where plain keys have to go.
+    fn settings_toml(extra: &str) -> String {
+        let settings: String = toml::to_string(&Settings::default()).unwrap().lines().filter(|line| !line.starts_with("strategy")).map(|line| format!("{}\n", line)).collect();
+        format!("{}\n{}", extra, settings)
+    }
+
+    fn scratch_file(name: &str, content: &str) -> String {
+        let path = scratch_dir().join(name);
+        fs::write(&path, content).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn missing_files_are_errors_to_recover_from() {
+        let missing = scratch_dir().join("missing.toml").to_string_lossy().to_string();
+        assert!(matches!(Settings::from_file(&missing), Err(HangmanError::Io { .. })));
+        assert!(matches!(SeedSalt::from_file(&missing), Err(HangmanError::Io { .. })));
+        assert!(matches!(read_word_list(&missing, &missing, None), Err(HangmanError::Io { .. })));
+        // no history is just no game in progress
+        assert!(History::from_file(&missing).unwrap().is_none());
+    }
+
+    #[test]
+    fn malformed_toml_is_a_parse_error() {
+        let bad = scratch_file("bad.toml", "salt = [oops");
+        assert!(matches!(Settings::from_file(&bad), Err(HangmanError::Parse { .. })));
+        assert!(matches!(SeedSalt::from_file(&bad), Err(HangmanError::Parse { .. })));
+        assert!(matches!(History::from_file(&bad), Err(HangmanError::Parse { .. })));
+        assert_eq!(SeedSalt::from_file(&scratch_file("salt.toml", "salt = 42")).unwrap(), 42);
+    }
+
+    #[test]
+    fn the_strategy_comes_from_the_settings_file() {
+        let fair = Settings::from_file(&scratch_file("fair.toml", &settings_toml("strategy = \"fair\""))).unwrap();
+        assert_eq!(fair.strategy, Strategy::Fair);
+        let default = Settings::from_file(&scratch_file("default.toml", &settings_toml(""))).unwrap();
+        assert_eq!(default.strategy, Strategy::Weighted);
+    }
+
+    #[test]
+    fn each_strategy_name_reads_as_its_strategy() {
+        for (name, strategy) in [("weighted", Strategy::Weighted), ("classic", Strategy::Classic), ("fair", Strategy::Fair), ("minimax", Strategy::Minimax)] {
+            let path = scratch_file("strategy.toml", &settings_toml(&format!("strategy = \"{}\"", name)));
+            assert_eq!(Settings::from_file(&path).unwrap().strategy, strategy, "{}", name);
+        }
+        // serde's message names all the ones there are
+        let path = scratch_file("cruel.toml", &settings_toml("strategy = \"cruel\""));
+        let Err(HangmanError::Parse { source, .. }) = Settings::from_file(&path) else { panic!("cruel was taken as a strategy") };
+        let message = source.to_string();
+        for name in ["cruel", "weighted", "classic", "fair", "minimax"] {
+            assert!(message.contains(name), "{}", message);
+        }
+    }
+
+    #[test]
+    fn frequencies_come_after_a_tab() {
+        let (words, exclusions) = word_files(&["cat\t12.5", "dog", "cow\t0"], &[]);
+        let list = read_weighted_word_list(&words, &exclusions, None).unwrap();
+        assert_eq!(list, [("CAT".to_string(), 12.5), ("COW".to_string(), 0.0), ("DOG".to_string(), 1.0)]);
+        assert_eq!(read_word_list(&words, &exclusions, None).unwrap(), ["CAT", "COW", "DOG"]);
+        let (bad, exclusions) = word_files(&["cat\tlots"], &[]);
+        assert!(matches!(read_weighted_word_list(&bad, &exclusions, None), Err(HangmanError::Parse { .. })));
+    }
+
+    // A history with a bit of everything in it.
+    fn full_history() -> History {
+        let mut game = crate::Game::new(vec!["CAT".to_string(), "COT".to_string(), "DOG".to_string()], History::new(7, 3), Settings::default(), 0).unwrap();
+        game.guess('O');
+        game.guess('Z');
+        History { seed_phrase: Some("hunter2".to_string()), started_at: Some(1_700_000_000), ..game.history_with_snapshot() }
+    }
+
+    #[test]
+    fn histories_round_trip_as_json_and_toml() {
+        let history = full_history();
+        for name in ["history.json", "history.toml"] {
+            let path = scratch_dir().join(name).to_string_lossy().to_string();
+            history.write_to_file(&path).unwrap();
+            assert_eq!(History::from_file(&path).unwrap(), Some(history.clone()), "{}", name);
+        }
+        let json = scratch_dir().join("history.json").to_string_lossy().to_string();
+        history.write_to_file(&json).unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&fs::read_to_string(&json).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn exclusions_are_left_out_whatever_their_case() {
+        let (words, exclusions) = word_files(&["apple", "Berry", "CHERRY", "date"], &["APPLE", "berry", "  Date  "]);
+        assert_eq!(read_word_list(&words, &exclusions, None).unwrap(), ["CHERRY"]);
+    }
+
+    #[test]
+    fn category_headers_sort_the_words_after_them() {
+        let settings = settings_for(&["sky", "# category: Animals", "cat", "dog", "#Category:fruit", "fig"]);
+        let (words, categories) = read_settings_categorized(&settings, None).unwrap();
+        assert_eq!(names(&words), ["CAT", "DOG", "FIG", "SKY"]);
+        assert_eq!(categories.keys().collect::<Vec<_>>(), ["ANIMALS", "FRUIT"]);
+        assert_eq!(categories["ANIMALS"], HashSet::from(["CAT".to_string(), "DOG".to_string()]));
+        assert!(!categories.values().any(|words| words.contains("SKY")));
+    }
+
+    #[test]
+    fn a_fixed_category_keeps_only_its_words() {
+        let settings = Settings { category: Some("animals".to_string()), ..settings_for(&["sky", "# category: Animals", "cat", "dog"]) };
+        let (words, _) = read_settings_categorized(&settings, None).unwrap();
+        assert_eq!(names(&words), ["CAT", "DOG"]);
+        let (words, _) = read_settings_categorized(&Settings { category: Some("random".to_string()), ..settings.clone() }, None).unwrap();
+        assert_eq!(words.len(), 3);
+        let missing = Settings { category: Some("birds".to_string()), ..settings };
+        assert!(matches!(read_settings_categorized(&missing, None), Err(HangmanError::InvalidSettings(_))));
+    }
+
+    #[test]
+    fn category_words_picks_out_the_category() {
+        let list = vec!["CAT".to_string(), "DOG".to_string(), "SKY".to_string()];
+        let categories = Categories::from([("ANIMALS".to_string(), HashSet::from(["CAT".to_string(), "DOG".to_string()]))]);
+        assert_eq!(category_words(&list, &categories, Some("ANIMALS")).unwrap(), ["CAT", "DOG"]);
+        assert_eq!(category_words(&list, &categories, None).unwrap(), list);
+        assert!(category_words(&list, &categories, Some("FRUIT")).is_err());
+    }
+
+    #[test]
+    fn slots_keep_their_own_guesses() {
+        let path = scratch_dir().join("history.toml").to_string_lossy().to_string();
+        let slot = |name: &str| Settings { history_path: path.clone(), slot: Some(name.to_string()), ..Settings::default() };
+        let words = || vec!["CAT".to_string(), "COT".to_string(), "DOG".to_string()];
+        assert_eq!(History::from_file(&slot("1").history_file()).unwrap(), None);
+
+        let mut one = crate::Game::new(words(), History::new(7, 3), slot("1"), 0).unwrap();
+        one.guess('O');
+        one.history().write_to_file(&slot("1").history_file()).unwrap();
+        let mut two = crate::Game::new(words(), History::new(7, 3), slot("2"), 0).unwrap();
+        two.guess('T');
+        two.guess('Z');
+        two.history().write_to_file(&slot("2").history_file()).unwrap();
+
+        let guesses = |name: &str| History::from_file(&slot(name).history_file()).unwrap().unwrap().guesses;
+        assert_eq!(guesses("1"), [hangman::Guess::Letter('O')]);
+        assert_eq!(guesses("2"), [hangman::Guess::Letter('T'), hangman::Guess::Letter('Z')]);
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    // A crash between writing the temporary file and renaming it leaves just the temporary file.
+    #[test]
+    fn an_interrupted_write_leaves_the_old_history() {
+        let path = scratch_dir().join("history.toml").to_string_lossy().to_string();
+        let history = full_history();
+        history.write_to_file(&path).unwrap();
+        let partial = HistoryFormat::Toml.serialize(&History::new(8, 4)).unwrap();
+        fs::write(format!("{}.tmp", path), &partial[..partial.len() / 2]).unwrap();
+        assert_eq!(History::from_file(&path).unwrap(), Some(history));
+
+        // and the next save writes over what was left
+        let next = History::new(8, 4);
+        next.write_to_file(&path).unwrap();
+        assert_eq!(History::from_file(&path).unwrap(), Some(next));
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+    }
+
+    #[test]
+    fn histories_without_a_status_are_in_progress() {
+        let path = scratch_dir().join("history.toml").to_string_lossy().to_string();
+        fs::write(&path, "rng_seed = 7\nletter_count = 3\nguesses = [\"O\"]\n").unwrap();
+        assert_eq!(History::from_file(&path).unwrap().unwrap().status, hangman::GameStatus::InProgress);
+    }
+
+    // All a version 1 file had, without even the version.
+    #[test]
+    fn version_1_histories_are_brought_up_to_date() {
+        let path = scratch_dir().join("history.toml").to_string_lossy().to_string();
+        fs::write(&path, "rng_seed = 7\nletter_count = 3\nguesses = [\"O\", \"Z\"]\n").unwrap();
+        let history = History::from_file(&path).unwrap().unwrap();
+        assert_eq!(history.version, hangman::HISTORY_VERSION);
+        assert_eq!((history.rng_seed, history.letter_count, history.wrong_count), (7, 3, 0));
+        assert_eq!(history.guesses.len(), 2);
+        assert_eq!((history.score, history.snapshot, history.started_at, history.elapsed_seconds), (None, None, None, 0));
+    }
+
+    #[test]
+    fn histories_from_a_newer_version_are_refused() {
+        let path = scratch_dir().join("history.toml").to_string_lossy().to_string();
+        let newer = hangman::HISTORY_VERSION + 1;
+        fs::write(&path, format!("version = {}\nrng_seed = 7\nletter_count = 3\nguesses = []\n", newer)).unwrap();
+        let result = History::from_file(&path);
+        assert!(matches!(result, Err(HangmanError::UnsupportedVersion { found, supported }) if found == newer && supported == hangman::HISTORY_VERSION), "{:?}", result);
+    }
+
+    // Settings has floats in it, so the two get compared as TOML.
+    #[test]
+    fn default_settings_files_read_back_the_same() {
+        let path = scratch_dir().join("settings.toml").to_string_lossy().to_string();
+        Settings::default().write_to_file(&path).unwrap();
+        let read = Settings::from_file(&path).unwrap();
+        assert_eq!(toml::to_string(&read).unwrap(), toml::to_string(&Settings::default()).unwrap());
+        assert!(read.validate().is_ok());
+        assert!(fs::read_to_string(&path).unwrap().starts_with("# Settings for manifold_hangman."));
+    }
+
+    #[test]
+    fn settings_files_are_never_overwritten() {
+        let path = scratch_dir().join("settings.toml").to_string_lossy().to_string();
+        fs::write(&path, "edited by hand").unwrap();
+        assert!(matches!(Settings::default().write_to_file(&path), Err(HangmanError::Io { .. })));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "edited by hand");
+    }
+
+    // What read_word_list checked with before is_all_ascii_alpha replaced it.
+    #[test]
+    fn the_letter_check_matches_the_old_regex() {
+        let old = Regex::new("^[a-zA-Z]+$").unwrap();
+        for word in ["", "cat", "CAT", "CaT", "cat1", "c at", "café", "naïve", "x", "_", "can't", "ÆON", "ZZZ\n", "\u{301}a"] {
+            assert_eq!(hangman::is_all_ascii_alpha(word), old.is_match(word), "{:?}", word);
+        }
+    }
+
+    // The same list twice over, once squeezed, for the .zst tests.
+    fn compressible_lists() -> (String, String, String) {
+        let (words, exclusions) = word_files(&["cat", "Dog", "COW", "dog", "emu", "yak2", "owl\t3.5", "# category: Farm", "pig"], &["EMU"]);
+        let zst = words.replace("words.txt", "words.txt.zst");
+        #[cfg(feature = "compression")]
+        fs::write(&zst, ruzstd::encoding::compress_to_vec(fs::File::open(&words).unwrap(), ruzstd::encoding::CompressionLevel::Fastest)).unwrap();
+        #[cfg(not(feature = "compression"))]
+        fs::write(&zst, b"(\xb5/\xfd").unwrap();
+        (words, zst, exclusions)
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_lists_read_the_same_as_plain_ones() {
+        let (words, zst, exclusions) = compressible_lists();
+        let plain = read_weighted_word_list(&words, &exclusions, None).unwrap();
+        assert_eq!(names(&plain), ["CAT", "COW", "DOG", "OWL", "PIG"]);
+        assert_eq!(read_weighted_word_list(&zst, &exclusions, None).unwrap(), plain);
+        assert_eq!(read_word_list(&zst, &exclusions, Some(3)).unwrap(), read_word_list(&words, &exclusions, Some(3)).unwrap());
+    }
+
+    #[cfg(not(feature = "compression"))]
+    #[test]
+    fn compressed_lists_need_the_feature() {
+        let (_, zst, exclusions) = compressible_lists();
+        assert!(matches!(read_word_list(&zst, &exclusions, None), Err(HangmanError::Io { .. })));
+    }
+
+    // DOG is in both, with a frequency in each, and the first file's is the one kept.
+    #[test]
+    fn merged_lists_share_one_copy_of_each_word() {
+        let dir = scratch_dir();
+        let (animals, pets, exclusions) = (dir.join("animals.txt"), dir.join("pets.txt"), dir.join("exclusions.txt"));
+        fs::write(&animals, "yak\ndog\t2\nowl").unwrap();
+        fs::write(&pets, "Dog\t9\ncat\nhamster").unwrap();
+        fs::write(&exclusions, "hamster").unwrap();
+        let paths = [animals, pets].map(|path| path.to_string_lossy().to_string());
+        let words = read_weighted_word_lists(&paths, &exclusions.to_string_lossy(), None).unwrap();
+        assert_eq!(words, [("CAT".to_string(), 1.0), ("DOG".to_string(), 2.0), ("OWL".to_string(), 1.0), ("YAK".to_string(), 1.0)]);
+
+        let settings = Settings { word_list_path: WordListPath::Many(paths.to_vec()), exclusions_list_path: exclusions.to_string_lossy().to_string(), ..Settings::default() };
+        assert_eq!(read_settings_word_list(&settings, None).unwrap(), words);
+    }
+
+    // The pattern sees the words uppercased, so it's written in capitals too.
+    #[test]
+    fn the_word_filter_keeps_only_matching_words() {
+        let settings = Settings { word_filter_regex: Some("ING$".to_string()), ..settings_for(&["sing", "Ring", "rang", "kingdom", "thing"]) };
+        assert_eq!(names(&read_settings_word_list(&settings, None).unwrap()), ["RING", "SING", "THING"]);
+    }
+
+    #[test]
+    fn an_invalid_word_filter_is_a_settings_error() {
+        let settings = Settings { word_filter_regex: Some("[A-Z".to_string()), ..settings_for(&["sing"]) };
+        match read_settings_word_list(&settings, None) {
+            Err(HangmanError::InvalidSettings(message)) => assert!(message.starts_with("word_filter_regex \"[A-Z\" isn't a valid regex"), "{}", message),
+            other => panic!("expected invalid settings, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn folded_accents_merge_with_the_plain_words() {
+        let lines = ["résumé", "resume", "RÉSUMÉ", "Ñandú", "straße", "naïve", "œuvre", "ωμέγα"];
+        let settings = Settings { normalize_accents: true, ..settings_for(&lines) };
+        // the Greek has nothing to fold into, so the alphabetic check still drops it
+        assert_eq!(names(&read_settings_word_list(&settings, None).unwrap()), ["NAIVE", "NANDU", "OEUVRE", "RESUME", "STRASSE"]);
+        let unfolded = Settings { normalize_accents: false, ..settings };
+        assert_eq!(names(&read_settings_word_list(&unfolded, None).unwrap()), ["RESUME"]);
+        assert_eq!(fold_accents("Crème Brûlée"), "Creme Brulee");
+    }
+
+    #[test]
+    fn allowed_letters_keep_only_words_made_of_them() {
+        let settings = Settings { allowed_letters: Some("etaoinshrdlu".to_string()), ..settings_for(&["stone", "Hound", "quiet", "train", "jazz", "Lunar"]) };
+        assert_eq!(names(&read_settings_word_list(&settings, None).unwrap()), ["HOUND", "LUNAR", "STONE", "TRAIN"]);
+    }
+
+    // The blocklist is matched whatever the case, like the exclusions, but is a file of its own.
+    #[test]
+    fn blocked_words_are_left_out_without_being_excluded() {
+        let settings = settings_for(&["heck", "Darn", "nice", "kind"]);
+        let blocklist = scratch_dir().join("blocklist.txt");
+        fs::write(&blocklist, "HECK\n  darn  ").unwrap();
+        assert_eq!(fs::read_to_string(&settings.exclusions_list_path).unwrap(), "");
+        let blocked = Settings { blocklist_path: Some(blocklist.to_string_lossy().to_string()), ..settings.clone() };
+        assert_eq!(names(&read_settings_word_list(&blocked, None).unwrap()), ["KIND", "NICE"]);
+        assert_eq!(names(&read_settings_word_list(&settings, None).unwrap()), ["DARN", "HECK", "KIND", "NICE"]);
+    }
+
+    #[test]
+    fn a_missing_blocklist_is_an_error() {
+        let settings = Settings { blocklist_path: Some(scratch_dir().join("nowhere.txt").to_string_lossy().to_string()), ..settings_for(&["kind"]) };
+        assert!(matches!(read_settings_word_list(&settings, None), Err(HangmanError::Io { .. })));
+    }
+
+    #[test]
+    fn reloading_only_rereads_the_words_when_they_could_change() {
+        let settings = settings_for(&["cat", "dog"]);
+        let mut dictionary = Dictionary::load(settings.clone(), None).unwrap();
+        let meaner = Settings { evil_factor: 3.0, ..settings.clone() };
+        assert_eq!(dictionary.reload(meaner).unwrap(), Reloaded { evil_changed: true, words_reloaded: false });
+        assert_eq!(dictionary.settings.evil_factor, 3.0);
+
+        let other = settings_for(&["owl", "emu", "yak"]);
+        let moved = Settings { word_list_path: other.word_list_path, evil_factor: 3.0, ..settings };
+        assert_eq!(dictionary.reload(moved).unwrap(), Reloaded { evil_changed: false, words_reloaded: true });
+        assert_eq!(dictionary.word_list, ["EMU", "OWL", "YAK"]);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapped_lists_read_the_same_as_plain_ones() {
+        let (words, exclusions) = word_files(&["cat", "Dog", "COW", "dog", "emu", "yak2", "owl\t3.5", "# category: Farm", "pig"], &["EMU"]);
+        let mapped = Content::of_file(&words).unwrap();
+        assert!(matches!(mapped, Content::Mapped(_)));
+        assert_eq!(mapped.as_str().unwrap(), Content::Read(fs::read_to_string(&words).unwrap()).as_str().unwrap());
+        assert_eq!(read_word_list(&words, &exclusions, None).unwrap(), ["CAT", "COW", "DOG", "OWL", "PIG"]);
+
+        fs::write(&words, b"cat\n\xff\xfe\n").unwrap();
+        let error = Content::of_file(&words).unwrap().as_str().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(read_word_list(&words, &exclusions, None).is_err());
+    }
+
+    #[test]
+    fn a_target_length_keeps_only_words_that_long() {
+        let (words, exclusions) = word_files(&["cat", "horse", "emu", "goat", "owl\t2", "ox", "Cat"], &[]);
+        let all = read_word_list(&words, &exclusions, None).unwrap();
+        for letter_count in 2..=5 {
+            let sized = read_word_list(&words, &exclusions, Some(letter_count)).unwrap();
+            assert!(sized.iter().all(|word| hangman::letter_len(word) == letter_count), "{:?}", sized);
+            let expected: Vec<&String> = all.iter().filter(|word| hangman::letter_len(word) == letter_count).collect();
+            assert_eq!(sized.iter().collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(read_word_list(&words, &exclusions, Some(3)).unwrap(), ["CAT", "EMU", "OWL"]);
+        assert!(read_word_list(&words, &exclusions, Some(6)).unwrap().is_empty());
+    }
+
+    // Won, lost and given up, the file says what the word was without replaying anything.
+    #[test]
+    fn finished_histories_keep_the_answer() {
+        let list = || ["CAT", "COT", "DOG", "PIG", "OWL"].map(String::from).to_vec();
+        let settings = Settings { max_wrong_guesses: Some(3), ..Settings::default() };
+        let mut won = crate::Game::new(list(), History::new(7, 3), Settings { max_wrong_guesses: Some(20), ..settings.clone() }, 0).unwrap();
+        for c in 'A'..='Z' { if !won.is_over() { won.guess(c); } }
+        let mut lost = crate::Game::new(list(), History::new(7, 3), settings.clone(), 0).unwrap();
+        for c in ['E', 'U', 'Y', 'Q'] { lost.guess(c); }
+        let mut forfeit = crate::Game::new(list(), History::new(7, 3), settings, 0).unwrap();
+        forfeit.guess('O');
+        forfeit.give_up();
+
+        for game in [won, lost, forfeit] {
+            assert!(game.is_over());
+            let path = scratch_dir().join("history.toml").to_string_lossy().to_string();
+            game.history().write_to_file(&path).unwrap();
+            let loaded = History::from_file(&path).unwrap().unwrap();
+            assert_eq!(loaded.answer.as_deref(), Some(game.outcome().unwrap().answer.as_str()));
+            assert!(list().contains(loaded.answer.as_ref().unwrap()));
+        }
+
+        let unfinished = crate::Game::new(list(), History::new(7, 3), Settings::default(), 0).unwrap();
+        assert_eq!(unfinished.history().answer, None);
+        assert!(!toml::to_string(unfinished.history()).unwrap().contains("answer"));
+    }
+
+    #[test]
+    fn definitions_come_after_a_tab() {
+        let path = scratch_file("definitions.tsv", "cat\ta small cat\nDOG \t  a loyal dog \nno tab here\nemu\t\ncat\ta second go\ncafé\ta coffee shop\n");
+        let definitions = read_definitions(&path, false).unwrap();
+        assert_eq!(definitions.get("CAT").map(String::as_str), Some("a small cat"));
+        assert_eq!(definitions.get("DOG").map(String::as_str), Some("a loyal dog"));
+        assert_eq!(definitions.get("CAFÉ").map(String::as_str), Some("a coffee shop"));
+        assert!(!definitions.contains_key("EMU") && !definitions.contains_key("NO TAB HERE"));
+        assert_eq!(definitions.len(), 3);
+        assert!(read_definitions(&path, true).unwrap().contains_key("CAFE"));
+        assert!(matches!(read_definitions("/no/such/definitions.tsv", false), Err(HangmanError::Io { .. })));
+    }
+}
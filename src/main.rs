@@ -1,75 +1,119 @@
-use regex::Regex;
-use serde::{Deserialize, Serialize};
-use std::fs;
+mod args;
+mod cli;
 
-mod hangman;
-use hangman::{Settings, History};
+use anyhow::Context;
+use clap::Parser;
+use std::path::Path;
 
-const SETTINGS_PATH: &str = "settings.toml";
+use manifold_hangman::{Settings, History};
+use manifold_hangman::{hangman, proof, share, transcript};
+use manifold_hangman::loading::{self, Dictionary, SeedSalt, read_proof};
 
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-struct SeedSalt {
-    salt: u64,
-}
-
-impl SeedSalt {
-    fn from_file(path: &str) -> u64 {
-        let content = fs::read_to_string(path)
-            .expect("Failed to read seed salt file");
-        let sd: SeedSalt = toml::from_str(&content)
-            .expect("Failed to parse seed salt file");
-
-        sd.salt
+fn run() -> anyhow::Result<()> {
+    let args = args::Args::parse();
+    if let Some(args::Command::Init) = args.command {
+        Settings::default().write_to_file(&args.settings)?;
+        println!("Wrote default settings to {}. Edit the paths in it to point at your files, then run again.", args.settings);
+        return Ok(());
     }
-}
-
-impl Settings {
-    /// Load settings from a TOML file, panicking on error
-    pub fn from_file(path: &str) -> Self {
-        let content = fs::read_to_string(path)
-            .expect("Failed to read settings file");
-        toml::from_str(&content)
-            .expect("Failed to parse settings file")
+    if !Path::new(&args.settings).exists() {
+        return Err(anyhow::Error::msg(format!("No settings file at {}; run with `init` to write a default one", args.settings)));
     }
-}
-
-impl History {
-    pub fn from_file(path: &str) -> Option<Self> {
-        let content = fs::read_to_string(path).ok();
-        content.map(|c| toml::from_str(&c)
-            .expect("Failed to parse history file"))
+    let settings = read_settings(&args)?;
+    cli::init_logging(&settings);
+    let playing = args.command.is_none() && args.verify.is_none() && args.diff.is_empty();
+    let history = if playing { read_history(&args, &settings)? } else { None };
+    // A game whose length we already know only needs words that long, which saves loading
+    // the rest of a big dictionary. Endless sessions go on to other lengths, so need them all.
+    let letter_count = match &history {
+        _ if args.endless => None,
+        Some(history) => Some(history.letter_count),
+        None if settings.difficulty.is_none() => args.letters,
+        None => None,
+    };
+    let mut dictionary = Dictionary::load(settings.clone(), letter_count)?;
+    if dictionary.word_list.is_empty() && letter_count.is_some() {
+        // nothing that long, so load the rest after all to say which lengths there are
+        dictionary = Dictionary::load(settings, None)?;
     }
-}
-
-fn read_word_list(word_path: &str, exclusions_path: &str) -> Vec<String> {
-    let alphabetic_regex = Regex::new("^[a-zA-Z]+$").unwrap();
-    let exclusions_content = fs::read_to_string(exclusions_path)
-        .expect("Failed to read exclusions file");
-    let mut exclusions = Vec::new();
-    for ex in exclusions_content.lines() {
-        if !alphabetic_regex.is_match(ex) {continue;}
-        exclusions.push(ex);
+    let Dictionary { settings, word_list, weights, categories, .. } = &dictionary;
+    if let Some(args::Command::Lengths) = args.command {
+        for (letter_count, words) in hangman::length_histogram(word_list) {
+            println!("{} letters: {} words", letter_count, words);
+        }
+        return Ok(());
+    }
+    if let Some(path) = &args.verify {
+        let proof = read_proof(path)?;
+        let words = loading::category_words(word_list, categories, proof.category.as_deref())?;
+        if proof::verify(&proof, &words, weights.clone(), settings) {
+            println!("The proof checks out: {} was the word.", proof.answer);
+        } else {
+            println!("The proof does NOT check out against this dictionary and these settings.");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let rng_salt = SeedSalt::from_file(&settings.salt_file_path)?;
+    if let [a, b] = args.diff.as_slice() {
+        let read = |path: &String| -> anyhow::Result<History> {
+            let history = History::from_file(path)?.with_context(|| format!("No history file at {}", path))?;
+            if proof::is_seed_hidden(&history) {
+                return Err(anyhow::Error::msg(format!("The seed in {} stays hidden until the game's over, so it can't be replayed yet", path)));
+            }
+            Ok(history)
+        };
+        let (a_history, b_history) = (read(a)?, read(b)?);
+        if a_history.category != b_history.category {
+            return Err(anyhow::Error::msg(format!("{} and {} are from different categories", a, b)));
+        }
+        let words = loading::category_words(word_list, categories, a_history.category.as_deref())?;
+        let report = transcript::diff_histories(&a_history, &b_history, &words, weights.clone(), settings, rng_salt)?;
+        cli::print_diff(&report, a, b);
+        return Ok(());
     }
+    let start = cli::StartOptions { seed: args.seed.clone(), letter_count: args.letters, random_seed: args.random_seed, guesses_path: args.guesses.clone() };
+    if args.replay.is_some() {
+        // read_history errors rather than find nothing to replay
+        let history = history.context("No game to replay")?;
+        let words = loading::category_words(word_list, categories, history.category.as_deref())?;
+        cli::step_through(words, weights.clone(), history, settings, rng_salt)?;
+    } else if args.endless {
+        cli::play_endless(&mut dictionary, history, rng_salt, &start, || read_settings(&args))?;
+    } else {
+        let mut moves = cli::Moves::new(&start)?;
+        cli::play_game(&dictionary, history, rng_salt, &start, &mut moves)?;
+    }
+    Ok(())
+}
 
-    let words_content = fs::read_to_string(word_path)
-        .expect("Failed to read word list file");
-    let mut words = Vec::new();
-    for word in words_content.lines() {
-        if !alphabetic_regex.is_match(word) {continue;}
-        if exclusions.contains(&word) {continue;}   //skip excluded words
-        words.push(word.to_string().to_uppercase())
+// The game in progress, or the one being imported or replayed.
+fn read_history(args: &args::Args, settings: &Settings) -> anyhow::Result<Option<History>> {
+    if let Some(path) = &args.replay {
+        let history = History::from_file(path)?.with_context(|| format!("No history file at {}", path))?;
+        return Ok(Some(history));
     }
-    words.sort();
-    words.dedup();
+    let history = History::from_file(&settings.history_file())?;
+    Ok(match &args.import {
+        Some(_) if history.is_some() => {
+            return Err(anyhow::Error::msg(format!("There's already a game in {}; finish or delete it before importing another", settings.history_file())));
+        }
+        Some(code) => Some(share::decode_game(code)?),
+        None => history,
+    })
+}
 
-    words
+// The settings file with the command line on top. Endless sessions read it again between games.
+fn read_settings(args: &args::Args) -> anyhow::Result<Settings> {
+    let mut settings = Settings::from_file(&args.settings)?;
+    args.apply(&mut settings);
+    settings.validate().context("Invalid settings")?;
+    Ok(settings)
 }
 
 fn main() {
-    let settings = Settings::from_file(SETTINGS_PATH);
-    let word_list = read_word_list(&settings.word_list_path, &settings.exclusions_list_path);
-    let rng_salt = SeedSalt::from_file(&settings.salt_file_path);
-    let history = History::from_file(&settings.history_path);
-    hangman::play_game(word_list, history, &settings, rng_salt)
+    if let Err(e) = run() {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::game::Game;
+use crate::hangman::{GameStatus, Guess, History, Revealed, Settings, WordWeights};
+use crate::seed;
+use crate::transcript::TurnRecord;
+
+// Everything needed to check a finished game was played straight: with the salt out in the
+// open, anyone with the same dictionary and settings can replay it and see the adversary
+// made exactly the choices its weights and rng called for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proof {
+    pub rng_seed: u64,
+    pub salt: u64,
+    // what the player was shown before the game began; see commitment()
+    pub commitment: u64,
+    pub letter_count: usize,
+    // the letters shown before the first guess, which the checking starts from rather than
+    // picking its own, since that would depend on settings.reveal_hints being the same
+    #[serde(default)]
+    pub pre_revealed: Vec<Revealed>,
+    pub guesses: Vec<Guess>,
+    // what each guess revealed, so every one of the adversary's choices gets checked
+    pub revealed: Vec<String>,
+    pub answer: String,
+    // the category of the word lists it was played from, if it had one, so the checking
+    // can use the same words; see loading::category_words
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+// Shown at the start so the salt can't be swapped afterwards. This is FNV, so it only keeps
+// honest people honest; it's no use against someone willing to search for a collision.
+pub fn commitment(salt: u64, rng_seed: u64) -> u64 {
+    seed::stable_hash(&format!("{}:{}", salt, rng_seed))
+}
+
+// The history as it's saved mid-game in hidden seed mode: the seed swapped for its
+// commitment, and without the seed phrase or a snapshot, which would give it away too.
+pub fn hide_seed(history: &History, salt: u64) -> History {
+    History {
+        rng_seed: 0,
+        seed_phrase: None,
+        snapshot: None,
+        seed_commitment: Some(commitment(salt, history.rng_seed)),
+        ..history.clone()
+    }
+}
+
+// Whether the seed is only there as a commitment, i.e. it was saved by hide_seed.
+pub fn is_seed_hidden(history: &History) -> bool {
+    history.seed_commitment.is_some() && history.status == GameStatus::InProgress
+}
+
+// Puts the seed back into a hidden seed history, if it's the one committed to.
+pub fn reveal_seed(history: &mut History, rng_seed: u64, salt: u64) -> bool {
+    if history.seed_commitment != Some(commitment(salt, rng_seed)) {
+        return false;
+    }
+    history.rng_seed = rng_seed;
+    true
+}
+
+// For a finished hidden seed game: whether the seed it ended up revealing is the one it
+// committed to at the start.
+pub fn check_revealed_seed(history: &History, salt: u64) -> bool {
+    !is_seed_hidden(history) && history.seed_commitment == Some(commitment(salt, history.rng_seed))
+}
+
+impl Proof {
+    // records is the game's transcript (see transcript::transcript). None until the game is over.
+    pub fn new(game: &Game, salt: u64, records: &[TurnRecord]) -> Option<Self> {
+        let outcome = game.outcome()?;
+        let history = game.history();
+        Some(Proof {
+            rng_seed: history.rng_seed,
+            salt,
+            commitment: commitment(salt, history.rng_seed),
+            letter_count: history.letter_count,
+            pre_revealed: history.pre_revealed.clone(),
+            guesses: outcome.guesses,
+            revealed: records.iter().map(|r| r.word_info.clone()).collect(),
+            answer: outcome.answer,
+            category: history.category.clone(),
+        })
+    }
+}
+
+// Replays the proof's guesses, checking each reveals what the proof says it did and that
+// the game ends on the same answer.
+// word_list, weights and settings have to be what the game was played with.
+pub fn verify(proof: &Proof, word_list: &[String], weights: Option<Arc<WordWeights>>, settings: &Settings) -> bool {
+    if proof.commitment != commitment(proof.salt, proof.rng_seed) {
+        return false;
+    }
+    let history = History { pre_revealed: proof.pre_revealed.clone(), ..History::new(proof.rng_seed, proof.letter_count) };
+    let game = match weights {
+        Some(weights) => Game::with_weights(word_list.to_vec(), weights, history, settings.clone(), proof.salt),
+        None => Game::new(word_list.to_vec(), history, settings.clone(), proof.salt),
+    };
+    let Ok(mut game) = game else { return false };
+    if proof.revealed.len() != proof.guesses.len() { return false; }
+    // one at a time, since a game that was over before the last guess isn't genuine either
+    for (guess, revealed) in proof.guesses.iter().zip(&proof.revealed) {
+        if game.is_over() { return false; }
+        game.apply(guess.clone());
+        if game.revealed() != revealed { return false; }
+    }
+    game.outcome().is_some_and(|outcome| outcome.answer == proof.answer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::transcript;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    const ANIMALS: [&str; 7] = ["CAT", "DOG", "COW", "OWL", "EMU", "YAK", "PIG"];
+
+    // A game of ANIMALS played through the alphabet, and its proof.
+    fn finished() -> (Game, Proof) {
+        let list = words(&ANIMALS);
+        let mut game = Game::new(list.clone(), History::new(3, 3), Settings::default(), 9).unwrap();
+        for c in 'A'..='Z' {
+            if game.is_over() { break; }
+            game.guess(c);
+        }
+        let records = transcript(game.history(), &list, None, &Settings::default(), 9).unwrap();
+        let proof = Proof::new(&game, 9, &records).unwrap();
+        (game, proof)
+    }
+
+    #[test]
+    fn genuine_games_pass() {
+        let (game, proof) = finished();
+        assert_eq!(proof.answer, game.revealed());
+        assert!(verify(&proof, &words(&ANIMALS), None, &Settings::default()));
+    }
+
+    #[test]
+    fn tampered_proofs_fail() {
+        let (_, proof) = finished();
+        let list = words(&ANIMALS);
+        // a different letter at the end doesn't finish the word
+        let mut last_changed = proof.clone();
+        *last_changed.guesses.last_mut().unwrap() = Guess::Letter('Z');
+        assert!(!verify(&last_changed, &list, None, &Settings::default()));
+        let mut shortened = proof.clone();
+        shortened.guesses.pop();
+        shortened.revealed.pop();
+        assert!(!verify(&shortened, &list, None, &Settings::default()));
+        let mut misreported = proof.clone();
+        misreported.revealed[0] = "X__".to_string();
+        assert!(!verify(&misreported, &list, None, &Settings::default()));
+        let other = ANIMALS.iter().find(|&&w| w != proof.answer).unwrap();
+        assert!(!verify(&Proof { answer: other.to_string(), ..proof.clone() }, &list, None, &Settings::default()));
+        assert!(!verify(&Proof { salt: 10, ..proof.clone() }, &list, None, &Settings::default()));
+        assert!(!verify(&Proof { salt: 10, commitment: commitment(10, proof.rng_seed), ..proof }, &list, None, &Settings::default()));
+    }
+
+    #[test]
+    fn unfinished_games_have_no_proof() {
+        let mut game = Game::new(words(&ANIMALS), History::new(3, 3), Settings::default(), 9).unwrap();
+        game.guess('E');
+        assert!(Proof::new(&game, 9, &[]).is_none());
+    }
+
+    #[test]
+    fn only_the_committed_seed_reveals_a_hidden_one() {
+        let history = History { seed_phrase: Some("hunter2".to_string()), ..History::new(5598334587018733939, 3) };
+        let mut hidden = hide_seed(&history, 9);
+        assert_eq!((hidden.rng_seed, hidden.seed_phrase.as_deref()), (0, None));
+        assert!(is_seed_hidden(&hidden));
+        assert!(!reveal_seed(&mut hidden, 1, 9));
+        assert!(!reveal_seed(&mut hidden, history.rng_seed, 10));
+        assert!(reveal_seed(&mut hidden, history.rng_seed, 9));
+        assert_eq!(hidden.rng_seed, history.rng_seed);
+    }
+
+    // Checked with settings that don't pre-reveal anything, so it has to go by the proof's letters.
+    #[test]
+    fn proofs_carry_pre_revealed_letters() {
+        let list = words(&["CRANE", "CRATE", "TRACE", "BRINE", "SLATE", "GRAPE"]);
+        let settings = Settings::builder().reveal_hints(2).build().unwrap();
+        let mut game = Game::new(list.clone(), History::new(5, 5), settings.clone(), 77).unwrap();
+        for c in 'A'..='Z' {
+            if game.is_over() { break; }
+            game.guess(c);
+        }
+        let records = transcript(game.history(), &list, None, &settings, 77).unwrap();
+        let proof = Proof::new(&game, 77, &records).unwrap();
+        assert_eq!(proof.pre_revealed.len(), 2);
+        assert!(verify(&proof, &list, None, &Settings::default()));
+    }
+}
@@ -0,0 +1,114 @@
+use std::iter;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// FNV-1a. std's hashers are free to change between Rust releases, and seeds
+// have to come out the same forever, so we roll our own.
+pub fn stable_hash(s: &str) -> u64 {
+    fnv_extend(FNV_OFFSET, s.bytes())
+}
+
+// Fingerprint of a whole word list, order included. A newline goes between words
+// so ["AB", "C"] and ["A", "BC"] come out different.
+pub fn word_list_hash(words: impl IntoIterator<Item = impl AsRef<str>>) -> u64 {
+    words.into_iter().fold(FNV_OFFSET, |hash, word| fnv_extend(hash, word.as_ref().bytes().chain(iter::once(b'\n'))))
+}
+
+pub fn bytes_hash(bytes: &[u8]) -> u64 {
+    fnv_extend(FNV_OFFSET, bytes.iter().copied())
+}
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+fn fnv_extend(mut hash: u64, bytes: impl Iterator<Item=u8>) -> u64 {
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// The UTC calendar date of a moment as "YYYY-MM-DD".
+// Converting days to a date is Howard Hinnant's civil_from_days, which saves pulling in chrono.
+pub fn utc_date(time: SystemTime) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let days = secs.div_euclid(86400);
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Everyone playing on the same date gets the same seed (the salt is still mixed in later).
+pub fn daily_seed(date: &str) -> u64 {
+    stable_hash(date)
+}
+
+// Seeds can be typed as a number or as any phrase you'll remember; phrases get hashed.
+pub fn parse_seed(s: &str) -> u64 {
+    s.parse().unwrap_or_else(|_| stable_hash(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    // Pinned like the daily seeds, so saved phrase games keep replaying the same.
+    #[test]
+    fn phrases_always_hash_to_the_same_seed() {
+        assert_eq!(parse_seed("hunter2"), 5598334587018733939);
+        assert_eq!(parse_seed("hunter2"), stable_hash("hunter2"));
+        assert_ne!(parse_seed("hunter3"), parse_seed("hunter2"));
+        assert_ne!(parse_seed("Hunter2"), parse_seed("hunter2"));
+    }
+
+    #[test]
+    fn numeric_seeds_parse_directly() {
+        assert_eq!(parse_seed("0"), 0);
+        assert_eq!(parse_seed("42"), 42);
+        assert_eq!(parse_seed("18446744073709551615"), u64::MAX);
+        // one past the largest u64 is just a phrase
+        assert_eq!(parse_seed("18446744073709551616"), stable_hash("18446744073709551616"));
+    }
+
+    #[test]
+    fn utc_dates_of_fixed_moments() {
+        assert_eq!(utc_date(UNIX_EPOCH), "1970-01-01");
+        assert_eq!(utc_date(at(951_782_400)), "2000-02-29");
+        assert_eq!(utc_date(at(1_700_000_000)), "2023-11-14");
+        // the last second of a day is still that day
+        assert_eq!(utc_date(at(86_399)), "1970-01-01");
+        assert_eq!(utc_date(UNIX_EPOCH - Duration::from_secs(1)), "1969-12-31");
+    }
+
+    // Pinned, since every daily game anyone has played depends on it never changing.
+    #[test]
+    fn daily_seeds_stay_the_same() {
+        assert_eq!(daily_seed("2024-03-01"), 15533343416502021627);
+        assert_eq!(daily_seed(&utc_date(at(1_709_294_400))), daily_seed("2024-03-01"));
+        assert_ne!(daily_seed("2024-03-02"), daily_seed("2024-03-01"));
+    }
+
+    #[test]
+    fn word_list_hashes_see_order_and_word_breaks() {
+        assert_eq!(word_list_hash(["CAT", "DOG"]), word_list_hash(vec!["CAT".to_string(), "DOG".to_string()]));
+        assert_ne!(word_list_hash(["CAT", "DOG"]), word_list_hash(["DOG", "CAT"]));
+        assert_ne!(word_list_hash(["AB", "C"]), word_list_hash(["A", "BC"]));
+        assert_ne!(word_list_hash(["CAT"]), word_list_hash(["CAT", "DOG"]));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::error::{HangmanError, Result};
+use crate::hangman::{Guess, History, Revealed, MAX_LETTERS};
+use crate::seed;
+
+// Game codes: a game's seed, length and guesses as one string that can be pasted to
+// someone else. With the same dictionary, salt and settings they get the same adversary.
+//
+// The bytes are a format version, the seed (8 bytes, little endian), the letter count,
+// then a line of the letters shown from the start as position and letter (e.g. "0A 3T"),
+// a line of the guesses separated by spaces, a line with the category (empty if there
+// isn't one), and a checksum byte at the end so a mistyped code gets refused instead of
+// quietly being some other game. That all goes out as unpadded base32, which survives
+// being read out loud or typed in any case.
+// Version 1 codes had just the guesses and version 2 ones no category; both are still read.
+const CODE_VERSION: u8 = 3;
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub fn encode_game(history: &History) -> String {
+    let mut bytes = vec![CODE_VERSION];
+    bytes.extend(history.rng_seed.to_le_bytes());
+    bytes.push(history.letter_count as u8);    // at most MAX_LETTERS, which fits
+    let pre_revealed: Vec<String> = history.pre_revealed.iter().map(|r| format!("{}{}", r.position, r.letter)).collect();
+    let guesses: Vec<String> = history.guesses.iter().map(Guess::to_string).collect();
+    let category = history.category.as_deref().unwrap_or("");
+    bytes.extend(format!("{}\n{}\n{}", pre_revealed.join(" "), guesses.join(" "), category).bytes());
+    bytes.push(checksum(&bytes));
+    to_base32(&bytes)
+}
+
+// Only the seed, length, pre-revealed letters, guesses and category come back; everything
+// else is a new History's default.
+pub fn decode_game(code: &str) -> Result<History> {
+    let bad = |why: &str| HangmanError::InvalidGameCode(why.to_string());
+    let bytes = from_base32(code.trim()).ok_or_else(|| bad("it has been mistyped, or isn't one at all"))?;
+    let Some((&sum, bytes)) = bytes.split_last() else { return Err(bad("it's empty")) };
+    if bytes.len() < 10 || checksum(bytes) != sum {
+        return Err(bad("it's been cut short or mistyped"));
+    }
+    let version = bytes[0];
+    if !(1..=CODE_VERSION).contains(&version) {
+        return Err(bad("it's from a different version"));
+    }
+    let rng_seed = u64::from_le_bytes(bytes[1..9].try_into().unwrap());
+    let letter_count = bytes[9] as usize;
+    if letter_count == 0 || letter_count > MAX_LETTERS {
+        return Err(bad("its word length is out of range"));
+    }
+    let text = std::str::from_utf8(&bytes[10..]).map_err(|_| bad("its guesses aren't text"))?;
+    let (pre_revealed, rest) = if version == 1 {
+        ("", text)
+    } else {
+        text.split_once('\n').ok_or_else(|| bad("it's missing the letters shown at the start"))?
+    };
+    let (guesses, category) = if version < 3 {
+        (rest, "")
+    } else {
+        rest.split_once('\n').ok_or_else(|| bad("it's missing the category"))?
+    };
+    let pre_revealed = pre_revealed.split_whitespace()
+        .map(|r| parse_revealed(r, letter_count).ok_or_else(|| bad("a letter shown at the start is out of place")))
+        .collect::<Result<Vec<_>>>()?;
+    let guesses = guesses.split_whitespace()
+        .map(|g| Guess::try_from(g.to_string()))
+        .collect::<Result<Vec<_>>>()?;
+    let category = (!category.is_empty()).then(|| category.to_string());
+    Ok(History { guesses, pre_revealed, category, ..History::new(rng_seed, letter_count) })
+}
+
+// e.g. "3T" for a T in the fourth place.
+fn parse_revealed(text: &str, letter_count: usize) -> Option<Revealed> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let position: usize = text[..split].parse().ok()?;
+    let mut letters = text[split..].chars();
+    let letter = letters.next().filter(|c| c.is_alphabetic())?;
+    (letters.next().is_none() && position < letter_count).then_some(Revealed { position, letter })
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    seed::bytes_hash(bytes) as u8
+}
+
+fn to_base32(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            code.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        code.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    code
+}
+
+// None if there's anything in it that isn't base32, or the padding bits at the end aren't
+// zero, so every game has just the one code.
+fn from_base32(code: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in code.chars() {
+        let value = ALPHABET.iter().position(|&a| a as char == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    (buffer & ((1 << bits) - 1) == 0).then_some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hangman::Revealed;
+
+    #[test]
+    fn codes_round_trip() {
+        let history = History {
+            guesses: vec![Guess::Letter('E'), Guess::Letter('A'), Guess::Hint, Guess::Word("CAT".to_string())],
+            ..History::new(u64::MAX - 6, 3)
+        };
+        let code = encode_game(&history);
+        assert!(code.bytes().all(|b| ALPHABET.contains(&b)), "{}", code);
+        let decoded = decode_game(&code).unwrap();
+        assert_eq!((decoded.rng_seed, decoded.letter_count, &decoded.guesses), (history.rng_seed, 3, &history.guesses));
+        assert_eq!(encode_game(&decoded), code);
+        // typed in lowercase, with a stray newline
+        assert_eq!(decode_game(&format!("{}\n", code.to_lowercase())).unwrap(), decoded);
+    }
+
+    #[test]
+    fn malformed_codes_are_refused() {
+        let code = encode_game(&History { guesses: vec![Guess::Letter('E')], ..History::new(42, 4) });
+        let mistyped: String = code.chars().enumerate().map(|(i, c)| if i == 3 { if c == 'A' { 'B' } else { 'A' } } else { c }).collect();
+        for bad in ["", "hello world", "1", &code[..code.len() - 4], &mistyped] {
+            assert!(matches!(decode_game(bad), Err(HangmanError::InvalidGameCode(_))), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn codes_keep_pre_revealed_letters() {
+        let history = History {
+            pre_revealed: vec![Revealed { position: 0, letter: 'C' }, Revealed { position: 3, letter: 'N' }],
+            guesses: vec![Guess::Letter('E'), Guess::Hint],
+            ..History::new(99, 5)
+        };
+        let decoded = decode_game(&encode_game(&history)).unwrap();
+        assert_eq!(decoded.pre_revealed, history.pre_revealed);
+        assert_eq!(decoded.guesses, history.guesses);
+    }
+
+    #[test]
+    fn version_1_codes_still_decode() {
+        let mut bytes = vec![1];
+        bytes.extend(42u64.to_le_bytes());
+        bytes.push(4);
+        bytes.extend(b"E T");
+        bytes.push(checksum(&bytes));
+        let history = decode_game(&to_base32(&bytes)).unwrap();
+        assert_eq!(history.guesses, vec![Guess::Letter('E'), Guess::Letter('T')]);
+        assert!(history.pre_revealed.is_empty());
+    }
+
+    #[test]
+    fn version_2_codes_still_decode() {
+        let mut bytes = vec![2];
+        bytes.extend(42u64.to_le_bytes());
+        bytes.push(4);
+        bytes.extend(b"1A\nE T");
+        bytes.push(checksum(&bytes));
+        let history = decode_game(&to_base32(&bytes)).unwrap();
+        assert_eq!(history.pre_revealed, vec![Revealed { position: 1, letter: 'A' }]);
+        assert_eq!(history.guesses, vec![Guess::Letter('E'), Guess::Letter('T')]);
+        assert_eq!(history.category, None);
+    }
+
+    // The category decides which words there are, so a code without it would be another game.
+    #[test]
+    fn codes_keep_the_category() {
+        let history = History {
+            category: Some("FARM ANIMALS".to_string()),
+            guesses: vec![Guess::Letter('O')],
+            ..History::new(7, 3)
+        };
+        let decoded = decode_game(&encode_game(&history)).unwrap();
+        assert_eq!(decoded.category.as_deref(), Some("FARM ANIMALS"));
+        assert_eq!(decoded.guesses, history.guesses);
+        assert_eq!(decode_game(&encode_game(&History::new(7, 3))).unwrap().category, None);
+    }
+
+    #[test]
+    fn pre_revealed_letters_past_the_end_are_refused() {
+        let history = History { pre_revealed: vec![Revealed { position: 5, letter: 'C' }], ..History::new(99, 5) };
+        assert!(matches!(decode_game(&encode_game(&history)), Err(HangmanError::InvalidGameCode(_))));
+    }
+}
@@ -0,0 +1,436 @@
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::hangman::{BucketSizes, Buckets, GuessSignature, Settings, WordWeights, guess_buckets, weighted_choice};
+
+// Decides which bucket of words survives a guess. Implement this to plug in your own adversary.
+// The buckets hold indices into words, which can have more in it than the words still left,
+// e.g. a whole dictionary shared between games; the words in the buckets are the ones that
+// count. buckets is never empty; choose_guess_outcome checks that before calling in.
+// Send so a Game can move between threads, e.g. one per request in a server.
+pub trait GuessStrategy: Send {
+    fn choose(&self, buckets: &Buckets, words: &[String], rng: &mut dyn RngCore) -> GuessSignature;
+
+    // Told before each guess how much of the word is showing, from 0 to 1. Most don't care.
+    fn set_revealed(&mut self, _fraction: f64) {}
+
+    // Some if bucket sizes are all it ever looks at, which saves working out every bucket's
+    // words when only the chosen one's are needed; see hangman::do_guess_by_size.
+    fn by_size(&self) -> Option<&dyn ChoosesBySize> {
+        None
+    }
+
+    // Some if the word was settled on up front, like Fair's. Hints come from it then, since a
+    // letter of some other word could rule it out.
+    fn committed_answer(&self) -> Option<&str> {
+        None
+    }
+}
+
+// A strategy that can pick from just the bucket sizes. It has to pick exactly what choose
+// would from the full buckets, using the rng the same way, or replays would come out different.
+pub trait ChoosesBySize {
+    fn choose_by_size(&self, sizes: &BucketSizes, rng: &mut dyn RngCore) -> GuessSignature;
+}
+
+// How the adversary picks which bucket of words survives a guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    // random pick weighted by evil_exponent and evil_factor
+    #[default]
+    Weighted,
+    // always keep the biggest bucket, i.e. textbook evil hangman
+    Classic,
+    // no adversary at all: a word is picked up front and guesses are judged against it
+    Fair,
+    // looks minimax_depth guesses ahead for the bucket that costs the player the most misses
+    Minimax,
+}
+
+impl Strategy {
+    // Fair games pick their word here, so this needs the game's own rng and
+    // has to happen before any guesses are made. word_list must be non-empty.
+    // With weights, fair games favour common words and weighted evil favours common buckets.
+    pub fn build(self, settings: &Settings, word_list: &[impl AsRef<str>], weights: Option<Arc<WordWeights>>, rng: &mut dyn RngCore) -> Box<dyn GuessStrategy> {
+        match self {
+            Strategy::Weighted => Box::new(WeightedEvil {
+                evil_exponent: settings.evil_exponent_start.unwrap_or(settings.evil_exponent),
+                evil_exponent_end: settings.evil_exponent_end.or(settings.evil_exponent_start).unwrap_or(settings.evil_exponent),
+                evil_factor: settings.evil_factor,
+                position_factor: settings.evil_position_factor,
+                weights,
+                revealed: 0.0,
+            }),
+            Strategy::Classic => Box::new(ClassicEvil),
+            Strategy::Fair => {
+                let answer = match weights {
+                    Some(weights) => {
+                        let mut options: Vec<(f64, &str)> = word_list.iter()
+                            .map(|w| (word_weight(&weights, w.as_ref()), w.as_ref()))
+                            .collect();
+                        // every frequency being zero is no reason not to have a word
+                        weighted_choice(&mut options, rng)
+                            .unwrap_or_else(|| word_list[rng.random_range(0..word_list.len())].as_ref())
+                    }
+                    None => word_list[rng.random_range(0..word_list.len())].as_ref(),
+                };
+                Box::new(Fair { answer: answer.to_string() })
+            }
+            Strategy::Minimax => Box::new(Minimax { depth: settings.minimax_depth }),
+        }
+    }
+}
+
+fn word_weight(weights: &WordWeights, word: &str) -> f64 {
+    weights.get(word).copied().unwrap_or(1.0)
+}
+
+// The original adversary: big buckets are likely, and each revealed letter makes a bucket
+// evil_factor times less likely. With weights, a bucket's odds also scale with how common its words are.
+// The exponent can also ramp from evil_exponent with nothing showing to evil_exponent_end
+// with the whole word out, so the adversary starts off lenient and gets meaner.
+#[derive(Debug, Clone)]
+pub struct WeightedEvil {
+    pub evil_exponent: f64,
+    // the same as evil_exponent for no ramp
+    pub evil_exponent_end: f64,
+    pub evil_factor: f64,
+    // see position_weight; 0 leaves it out
+    pub position_factor: f64,
+    pub weights: Option<Arc<WordWeights>>,
+    // how much of the word is showing, as last told by set_revealed
+    pub revealed: f64,
+}
+
+impl WeightedEvil {
+    // Where the ramp has got to; exactly evil_exponent when there isn't one.
+    pub fn current_exponent(&self) -> f64 {
+        self.evil_exponent + (self.evil_exponent_end - self.evil_exponent) * self.revealed
+    }
+
+    // A bucket's weight from its size and how many letters it reveals, before word weights
+    // or positions come into it.
+    fn size_weight(&self, sig: GuessSignature, bucket_size: usize) -> f64 {
+        (bucket_size as f64).powf(self.current_exponent()) / self.evil_factor.powf(sig.matches_count() as f64)
+    }
+
+    // How much to scale a bucket's weight by for where its letters go. Giving away a letter
+    // where most of the words have it anyway costs next to nothing, while one where few do
+    // narrows things down a lot, so each revealed position multiplies the weight by the
+    // share of words with that letter there, to the power of position_factor.
+    // The share is of every word in buckets, which are all the words still left.
+    pub fn position_weight(&self, sig: GuessSignature, bucket: &[usize], buckets: &Buckets, words: &[String]) -> f64 {
+        if self.position_factor == 0.0 || sig.is_empty() { return 1.0; }
+        // every word in the bucket has the guessed letter in the same places
+        let example: Vec<char> = words[bucket[0]].chars().collect();
+        let left = || buckets.values().flatten().map(|&i| &words[i]);
+        let total = left().count();
+        sig.decode(example.len()).enumerate()
+            .filter(|(_, revealed)| *revealed)
+            .map(|(position, _)| {
+                let with_letter = left().filter(|w| w.chars().nth(position) == Some(example[position])).count();
+                (with_letter as f64 / total as f64).powf(self.position_factor)
+            })
+            .product()
+    }
+}
+
+impl GuessStrategy for WeightedEvil {
+    fn choose(&self, buckets: &Buckets, words: &[String], rng: &mut dyn RngCore) -> GuessSignature {
+        let mut options = Vec::new();
+
+        for (sig, bucket) in buckets {
+            let mut weight = self.size_weight(*sig, bucket.len()) * self.position_weight(*sig, bucket, buckets, words);
+            if let Some(weights) = &self.weights {
+                weight *= bucket.iter().map(|&i| word_weight(weights, &words[i])).sum::<f64>();
+            }
+            options.push((weight, *sig));
+        }
+
+        // weighted choice sorts the options so we don't need to worry about inconsistent HashMap key orders.
+        // If the settings are extreme enough that no weight is usable, just play classic evil.
+        weighted_choice(&mut options, rng).unwrap_or_else(|| ClassicEvil.choose(buckets, words, rng))
+    }
+
+    fn set_revealed(&mut self, fraction: f64) {
+        self.revealed = fraction;
+    }
+
+    // Word weights and positions both need the words themselves.
+    fn by_size(&self) -> Option<&dyn ChoosesBySize> {
+        (self.weights.is_none() && self.position_factor == 0.0).then_some(self as &dyn ChoosesBySize)
+    }
+}
+
+// position_weight is always 1 here, and the weighted choice sees the same weights as choose
+// would, so the result is the same.
+impl ChoosesBySize for WeightedEvil {
+    fn choose_by_size(&self, sizes: &BucketSizes, rng: &mut dyn RngCore) -> GuessSignature {
+        let mut options: Vec<(f64, GuessSignature)> = sizes.iter().map(|(sig, n)| (self.size_weight(*sig, *n), *sig)).collect();
+        weighted_choice(&mut options, rng).unwrap_or_else(|| ClassicEvil.choose_by_size(sizes, rng))
+    }
+}
+
+// Biggest bucket wins. Ties go to whichever reveals fewer letters, and after that
+// the lowest signature, so the choice never depends on HashMap order.
+#[derive(Debug, Clone)]
+pub struct ClassicEvil;
+
+impl GuessStrategy for ClassicEvil {
+    fn choose(&self, buckets: &Buckets, _words: &[String], rng: &mut dyn RngCore) -> GuessSignature {
+        let sizes = buckets.iter().map(|(sig, bucket)| (*sig, bucket.len())).collect();
+        self.choose_by_size(&sizes, rng)
+    }
+
+    fn by_size(&self) -> Option<&dyn ChoosesBySize> {
+        Some(self)
+    }
+}
+
+impl ChoosesBySize for ClassicEvil {
+    fn choose_by_size(&self, sizes: &BucketSizes, _rng: &mut dyn RngCore) -> GuessSignature {
+        sizes.iter()
+            .max_by(|(sig1, n1), (sig2, n2)| {
+                n1.cmp(n2)
+                    .then(sig2.matches_count().cmp(&sig1.matches_count()))
+                    .then(sig2.cmp(sig1))
+            })
+            .map(|(sig, _)| *sig)
+            .expect("buckets should never be empty")
+    }
+}
+
+// Whichever bucket the committed answer landed in. answer has to be one of the game's words.
+#[derive(Debug, Clone)]
+pub struct Fair {
+    pub answer: String,
+}
+
+impl GuessStrategy for Fair {
+    fn choose(&self, buckets: &Buckets, words: &[String], _rng: &mut dyn RngCore) -> GuessSignature {
+        buckets.iter()
+            .find(|(_, bucket)| bucket.iter().any(|&i| words[i] == self.answer))
+            .map(|(sig, _)| *sig)
+            .expect("the answer should be in one of the buckets")
+    }
+
+    fn committed_answer(&self) -> Option<&str> {
+        Some(&self.answer)
+    }
+}
+
+// Keeps the bucket a perfect player would take the most misses to solve from. That gets
+// played out depth more guesses ahead, the player picking the letter that hurts least and
+// this the bucket that hurts most, after which a bucket's size stands in for the rest.
+// It's exponential in depth, so settings only allow 1 or 2.
+#[derive(Debug, Clone)]
+pub struct Minimax {
+    pub depth: u32,
+}
+
+// Past this many words, looking ahead gets slow and the size of a bucket is as good a guide.
+const LOOKAHEAD_WORDS: usize = 2000;
+
+impl GuessStrategy for Minimax {
+    // Ties go to the bigger bucket and then like ClassicEvil, so the rng is never needed.
+    fn choose(&self, buckets: &Buckets, words: &[String], _rng: &mut dyn RngCore) -> GuessSignature {
+        buckets.iter()
+            .map(|(sig, bucket)| {
+                let bucket_words: Vec<String> = bucket.iter().map(|&i| words[i].clone()).collect();
+                (misses_after(*sig) + misses_to_solve(&bucket_words, self.depth), bucket.len(), *sig)
+            })
+            .max_by(|(m1, n1, sig1), (m2, n2, sig2)| {
+                m1.total_cmp(m2)
+                    .then(n1.cmp(n2))
+                    .then(sig2.matches_count().cmp(&sig1.matches_count()))
+                    .then(sig2.cmp(sig1))
+            })
+            .map(|(_, _, sig)| sig)
+            .expect("buckets should never be empty")
+    }
+}
+
+fn misses_after(sig: GuessSignature) -> f64 {
+    if sig.is_empty() { 1.0 } else { 0.0 }
+}
+
+// How many more misses it takes to pin down which of the words it is, playing both sides
+// as well as possible for depth guesses and estimating from there. Only the letters that
+// split the words up are worth guessing; anything already guessed can't.
+fn misses_to_solve(words: &[String], depth: u32) -> f64 {
+    if words.len() <= 1 { return 0.0; }
+    if depth == 0 || words.len() > LOOKAHEAD_WORDS { return (words.len() as f64).log2(); }
+    ('A'..='Z')
+        .map(|letter| guess_buckets(words, letter))
+        .filter(|buckets| buckets.len() > 1)
+        .map(|buckets| buckets.iter()
+            .map(|(sig, bucket)| {
+                let bucket_words: Vec<String> = bucket.iter().map(|&i| words[i].clone()).collect();
+                misses_after(*sig) + misses_to_solve(&bucket_words, depth - 1)
+            })
+            .fold(f64::MIN, f64::max))
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+    use std::collections::HashMap;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    fn rng(seed: u64) -> ChaCha12Rng {
+        ChaCha12Rng::seed_from_u64(seed)
+    }
+
+    fn weighted(start: f64, end: f64) -> WeightedEvil {
+        WeightedEvil { evil_exponent: start, evil_exponent_end: end, evil_factor: 1.5, position_factor: 0.0, weights: None, revealed: 0.0 }
+    }
+
+    #[test]
+    fn exponent_ramps_with_the_word_revealed() {
+        let mut strategy = weighted(1.0, 3.0);
+        for (fraction, exponent) in [(0.0, 1.0), (0.5, 2.0), (1.0, 3.0)] {
+            strategy.set_revealed(fraction);
+            assert_eq!(strategy.current_exponent(), exponent, "at {} revealed", fraction);
+        }
+    }
+
+    // With the ends the same, how much is showing makes no difference to what gets picked.
+    #[test]
+    fn equal_ends_play_like_a_fixed_exponent() {
+        let words = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "ACT"]);
+        let buckets = guess_buckets(&words, 'T');
+        let fixed = weighted(2.0, 2.0);
+        let mut ramped = weighted(2.0, 2.0);
+        ramped.set_revealed(0.6);
+        for seed in 0..20 {
+            let a = fixed.choose(&buckets, &words, &mut rng(seed));
+            let b = ramped.choose(&buckets, &words, &mut rng(seed));
+            assert_eq!(a, b, "seed {}", seed);
+        }
+    }
+
+    // DOG PIG HEN OWL EMU have no T, against three with one at the end.
+    #[test]
+    fn classic_keeps_the_biggest_bucket() {
+        let words = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "ACT"]);
+        for seed in 0..5 {
+            assert_eq!(ClassicEvil.choose(&guess_buckets(&words, 'T'), &words, &mut rng(seed)), GuessSignature::empty());
+        }
+        assert_eq!(ClassicEvil.choose(&guess_buckets(&words, 'C'), &words, &mut rng(0)), GuessSignature::empty());
+    }
+
+    #[test]
+    fn classic_ties_go_to_fewer_letters_then_the_lowest_signature() {
+        let sig = GuessSignature::from_bits;
+        let sizes: BucketSizes = HashMap::from([(sig(0b11), 2), (sig(0b100), 2), (sig(0b1), 1)]);
+        assert_eq!(ClassicEvil.choose_by_size(&sizes, &mut rng(0)), sig(0b100));
+        let sizes: BucketSizes = HashMap::from([(sig(0b10), 2), (sig(0b100), 2), (sig(0b1), 2)]);
+        assert_eq!(ClassicEvil.choose_by_size(&sizes, &mut rng(0)), sig(0b1));
+    }
+
+    fn answer(strategy: &dyn GuessStrategy) -> String {
+        strategy.committed_answer().unwrap().to_string()
+    }
+
+    // With one word far more common than the rest, fair games nearly always pick it, and a
+    // fixed seed always picks the same one.
+    #[test]
+    fn fair_games_favour_common_words() {
+        let words = words(&["CAT", "COT", "DOG", "PIG"]);
+        let weights = Arc::new(WordWeights::from([("DOG".to_string(), 1000.0), ("CAT".to_string(), 0.001), ("COT".to_string(), 0.001)]));
+        let settings = Settings::default();
+        let build = |seed| Strategy::Fair.build(&settings, &words, Some(Arc::clone(&weights)), &mut rng(seed));
+        let dogs = (0..50).filter(|&seed| answer(build(seed).as_ref()) == "DOG").count();
+        assert!(dogs >= 48, "only {} of 50 picked DOG", dogs);
+        for seed in 0..10 {
+            assert_eq!(answer(build(seed).as_ref()), answer(build(seed).as_ref()));
+        }
+        // all zero is no reason to go without a word
+        let zero = Arc::new(words.iter().map(|w| (w.clone(), 0.0)).collect::<WordWeights>());
+        assert!(words.contains(&answer(Strategy::Fair.build(&settings, &words, Some(zero), &mut rng(0)).as_ref())));
+    }
+
+    // COT and DOG share a bucket for O, and DOG's weight makes that the one to keep.
+    #[test]
+    fn weighted_evil_favours_buckets_of_common_words() {
+        let words = words(&["CAT", "COT", "DOG", "PIG", "HEN"]);
+        let buckets = guess_buckets(&words, 'O');
+        let mut strategy = weighted(1.0, 1.0);
+        let plain = (0..50).filter(|&seed| !strategy.choose(&buckets, &words, &mut rng(seed)).is_empty()).count();
+        strategy.weights = Some(Arc::new(WordWeights::from([("DOG".to_string(), 1000.0)])));
+        let common = (0..50).filter(|&seed| !strategy.choose(&buckets, &words, &mut rng(seed)).is_empty()).count();
+        assert!(common >= 48 && plain < 40, "O kept {} times unweighted and {} weighted", plain, common);
+    }
+
+    // Only fair commits to a word, and only minimax needs the words to choose.
+    #[test]
+    fn each_strategy_builds_its_own_adversary() {
+        let words = words(&["CAT", "COT", "DOG", "PIG", "HEN", "OWL", "EMU", "ACT"]);
+        let settings = Settings::default();
+        let build = |strategy: Strategy| strategy.build(&settings, &words, None, &mut rng(0));
+        let weighted = build(Strategy::Weighted);
+        assert!(weighted.committed_answer().is_none() && weighted.by_size().is_some());
+        let classic = build(Strategy::Classic);
+        assert!(classic.committed_answer().is_none());
+        for seed in 0..5 {
+            assert_eq!(classic.choose(&guess_buckets(&words, 'T'), &words, &mut rng(seed)), GuessSignature::empty());
+        }
+        assert!(words.contains(&answer(build(Strategy::Fair).as_ref())));
+        let minimax = build(Strategy::Minimax);
+        assert!(minimax.committed_answer().is_none() && minimax.by_size().is_none());
+    }
+
+    // CAT COT ACT keep a T but one more guess tells them apart, where a miss leaves DOG or
+    // PIG and still costs the player another miss to tell those two apart.
+    #[test]
+    fn minimax_gives_up_the_bigger_bucket_for_more_misses() {
+        let words = words(&["CAT", "COT", "DOG", "PIG", "ACT"]);
+        let buckets = guess_buckets(&words, 'T');
+        assert_eq!(ClassicEvil.choose(&buckets, &words, &mut rng(0)), GuessSignature::from_bits(0b100));
+        for depth in [1, 2] {
+            assert_eq!(Minimax { depth }.choose(&buckets, &words, &mut rng(0)), GuessSignature::empty(), "depth {}", depth);
+        }
+        assert_eq!(misses_to_solve(&words[2..4], 1), 1.0);
+        assert_eq!(misses_to_solve(&words[2..3], 2), 0.0);
+    }
+
+    // BOAT COAT and TOAD TOGA are the same size and show one T each, but STET ends in T
+    // too, so a T at the end gives less away than one at the front.
+    #[test]
+    fn positions_more_words_share_are_cheaper_to_reveal() {
+        let words = words(&["BOAT", "COAT", "TOAD", "TOGA", "STET"]);
+        let buckets = guess_buckets(&words, 'T');
+        let (end, front) = (GuessSignature::from_bits(0b1000), GuessSignature::from_bits(0b1));
+        let mut strategy = weighted(1.0, 1.0);
+        assert_eq!(strategy.position_weight(end, &buckets[&end], &buckets, &words), 1.0);
+        strategy.position_factor = 1.0;
+        assert_eq!(strategy.position_weight(end, &buckets[&end], &buckets, &words), 3.0 / 5.0);
+        assert_eq!(strategy.position_weight(front, &buckets[&front], &buckets, &words), 2.0 / 5.0);
+
+        strategy.position_factor = 2.0;
+        strategy.evil_factor = 1.0;
+        let picks: Vec<GuessSignature> = (0..200).map(|seed| strategy.choose(&buckets, &words, &mut rng(seed))).collect();
+        let count = |sig| picks.iter().filter(|&&picked| picked == sig).count();
+        assert!(count(end) > count(front) + 40, "end {} times, front {}", count(end), count(front));
+    }
+
+    // Without the position factor, choosing from the words and from the sizes agree.
+    #[test]
+    fn no_position_factor_plays_as_before() {
+        let words = words(&["BOAT", "COAT", "TOAD", "TOGA", "STET", "FROG"]);
+        let buckets = guess_buckets(&words, 'T');
+        let sizes: BucketSizes = buckets.iter().map(|(sig, bucket)| (*sig, bucket.len())).collect();
+        let strategy = weighted(1.0, 1.0);
+        for seed in 0..20 {
+            assert_eq!(strategy.choose(&buckets, &words, &mut rng(seed)), strategy.choose_by_size(&sizes, &mut rng(seed)), "seed {}", seed);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use crate::error::Result;
+use crate::game::Game;
+use crate::hangman::{Guess, History, Settings, WordWeights};
+
+// One move of a finished or in-progress game, as it looked at the time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnRecord {
+    pub guess: Guess,
+    // the word as revealed after this move
+    pub word_info: String,
+    pub remaining: usize,
+}
+
+// Replays the history move by move, the same way resuming a game does, and notes down
+// where each move left things. word_list is the whole dictionary, and weights and
+// rng_salt have to be what the game was played with or the adversary won't repeat itself.
+pub fn transcript(history: &History, word_list: &[String], weights: Option<Arc<WordWeights>>, settings: &Settings, rng_salt: u64) -> Result<Vec<TurnRecord>> {
+    let start = History { guesses: Vec::new(), snapshot: None, answer: None, ..history.clone() };
+    let mut game = match weights {
+        Some(weights) => Game::with_weights(word_list.to_vec(), weights, start, settings.clone(), rng_salt)?,
+        None => Game::new(word_list.to_vec(), start, settings.clone(), rng_salt)?,
+    };
+
+    let mut records = Vec::new();
+    for guess in &history.guesses {
+        game.apply(guess.clone());
+        records.push(TurnRecord {
+            guess: guess.clone(),
+            word_info: game.revealed().to_string(),
+            remaining: game.remaining(),
+        });
+    }
+    Ok(records)
+}
+
+// Where two games stopped going the same way, from diff_histories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffReport {
+    // how many moves from the start both games made the same, with the same results
+    pub shared: usize,
+    // each game's first move after those, None for one that had no more moves
+    pub a: Option<TurnRecord>,
+    pub b: Option<TurnRecord>,
+}
+
+impl DiffReport {
+    pub fn diverged(&self) -> bool {
+        self.a.is_some() || self.b.is_some()
+    }
+
+    // How many more words a had left than b after the moves where they parted, if both made one.
+    pub fn remaining_gap(&self) -> Option<i64> {
+        Some(self.a.as_ref()?.remaining as i64 - self.b.as_ref()?.remaining as i64)
+    }
+}
+
+// Replays both games, as transcript does, to find the first move where they differ, in the
+// guess or what it revealed. Two games with the same seed only part once the guesses do.
+pub fn diff_histories(a: &History, b: &History, word_list: &[String], weights: Option<Arc<WordWeights>>, settings: &Settings, rng_salt: u64) -> Result<DiffReport> {
+    let a = transcript(a, word_list, weights.clone(), settings, rng_salt)?;
+    let b = transcript(b, word_list, weights, settings, rng_salt)?;
+    let shared = a.iter().zip(&b).take_while(|(a, b)| a == b).count();
+    Ok(DiffReport { shared, a: a.get(shared).cloned(), b: b.get(shared).cloned() })
+}
+
+// One line per move, like the verbose replay prints.
+pub fn format_text(records: &[TurnRecord]) -> String {
+    records.iter().enumerate()
+        .map(|(n, r)| format!("Guess #{}: {}  Result: {}  Remaining Words: {}\n", n + 1, r.guess, r.word_info, r.remaining))
+        .collect()
+}
+
+// A Markdown table of the moves. Results go in backticks since "_" means emphasis there.
+pub fn format_markdown(records: &[TurnRecord]) -> String {
+    let mut table = String::from("| # | Guess | Result | Remaining Words |\n|---|---|---|---|\n");
+    for (n, r) in records.iter().enumerate() {
+        table += &format!("| {} | {} | `{}` | {} |\n", n + 1, r.guess, r.word_info, r.remaining);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words() -> Vec<String> {
+        ["CAT", "COT", "DOG", "PIG", "HEN", "OWL"].iter().map(|w| w.to_string()).collect()
+    }
+
+    fn played(letters: &str) -> Game {
+        let mut game = Game::new(words(), History::new(5, 3), Settings::default(), 0).unwrap();
+        for letter in letters.chars() {
+            game.guess(letter);
+        }
+        game
+    }
+
+    #[test]
+    fn the_transcript_ends_where_the_game_did() {
+        let game = played("OTZC");
+        let records = transcript(game.history(), &words(), None, &Settings::default(), 0).unwrap();
+        assert_eq!(records.len(), 4);
+        let last = records.last().unwrap();
+        assert_eq!(last.word_info, game.revealed());
+        assert_eq!(last.remaining, game.remaining());
+        assert!(records.windows(2).all(|pair| pair[0].remaining >= pair[1].remaining));
+    }
+
+    #[test]
+    fn text_and_markdown_have_a_line_a_move() {
+        let records = [TurnRecord { guess: Guess::Letter('E'), word_info: "_E_".to_string(), remaining: 1 }];
+        assert_eq!(format_text(&records), "Guess #1: E  Result: _E_  Remaining Words: 1\n");
+        assert!(format_markdown(&records).ends_with("| 1 | E | `_E_` | 1 |\n"));
+    }
+
+    #[test]
+    fn games_part_at_the_first_different_guess() {
+        let a = played("OT");
+        let b = played("OE");
+        let report = diff_histories(a.history(), b.history(), &words(), None, &Settings::default(), 0).unwrap();
+        assert!(report.diverged());
+        assert_eq!(report.shared, 1);
+        let (after_a, after_b) = (report.a.clone().unwrap(), report.b.clone().unwrap());
+        assert_eq!((after_a.guess, after_b.guess), (Guess::Letter('T'), Guess::Letter('E')));
+        assert_eq!((after_a.remaining, after_b.remaining), (a.remaining(), b.remaining()));
+        assert_eq!(report.remaining_gap(), Some(a.remaining() as i64 - b.remaining() as i64));
+
+        // a game that just stopped sooner parts where it stopped
+        let sooner = diff_histories(played("O").history(), a.history(), &words(), None, &Settings::default(), 0).unwrap();
+        assert_eq!(sooner.shared, 1);
+        assert!(sooner.a.is_none() && sooner.b.is_some());
+        assert_eq!(sooner.remaining_gap(), None);
+
+        let same = diff_histories(a.history(), a.history(), &words(), None, &Settings::default(), 0).unwrap();
+        assert!(!same.diverged());
+        assert_eq!(same.shared, 2);
+    }
+}